@@ -0,0 +1,410 @@
+use crate::sfx::{Channel, SoundHandle};
+use crate::sound_cache::{BufferedSound, SoundData};
+use rodio::{Decoder, OutputStream, OutputStreamHandle, Sink, Source};
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufReader};
+use std::rc::Rc;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+#[derive(Debug)]
+pub enum AudioError {
+    NoDevice(String),
+    Io { path: String, error: io::Error },
+    Decode { path: String, reason: String },
+}
+
+impl fmt::Display for AudioError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AudioError::NoDevice(reason) => write!(f, "no audio device available: {}", reason),
+            AudioError::Io { path, error } => write!(f, "could not read {}: {}", path, error),
+            AudioError::Decode { path, reason } => write!(f, "could not decode {}: {}", path, reason),
+        }
+    }
+}
+
+// De dónde salen las muestras: memoria ya decodificada o un archivo que se
+// decodifica mientras suena (para pistas largas como la música)
+#[derive(Clone)]
+pub enum SoundSource {
+    Buffered(Arc<SoundData>),
+    Stream(String),
+}
+
+// Eco simple: una copia del sonido retrasada y atenuada
+#[derive(Clone, Copy)]
+pub struct Reverb {
+    pub delay: Duration,
+    pub amount: f32, // Volumen de la copia, 0 a 1
+}
+
+// Qué se quiere reproducir; el backend decide cómo
+#[derive(Clone)]
+pub struct SoundRequest {
+    pub name: String,
+    pub source: SoundSource,
+    pub channel: Channel,
+    pub looped: bool,
+    pub clip: Option<(Duration, Duration)>, // Inicio y duración del fragmento
+    pub spatial: bool, // Se reproduce en estéreo con paneo ajustable
+    pub reverb: Option<Reverb>,
+}
+
+// Salida de audio intercambiable: el AudioManager lleva los canales y
+// volúmenes, el backend solo ejecuta las órdenes
+pub trait AudioBackend {
+    fn start(
+        &mut self,
+        handle: SoundHandle,
+        request: &SoundRequest,
+        volume: f32,
+        paused: bool,
+    ) -> Result<(), AudioError>;
+    fn stop(&mut self, handle: SoundHandle);
+    fn set_volume(&mut self, handle: SoundHandle, volume: f32);
+    fn pause(&mut self, handle: SoundHandle);
+    fn resume(&mut self, handle: SoundHandle);
+    // -1 = solo izquierda, 0 = centro, 1 = solo derecha
+    fn set_pan(&mut self, handle: SoundHandle, pan: f32);
+    fn is_finished(&mut self, handle: SoundHandle) -> bool;
+}
+
+// Backend real usando rodio
+pub struct RodioBackend {
+    _stream: OutputStream, // Mantenemos el OutputStream vivo
+    stream_handle: OutputStreamHandle,
+    sinks: HashMap<SoundHandle, Sink>,
+    pans: HashMap<SoundHandle, Arc<AtomicU32>>,
+}
+
+impl RodioBackend {
+    pub fn new() -> Result<Self, AudioError> {
+        let (stream, stream_handle) =
+            OutputStream::try_default().map_err(|e| AudioError::NoDevice(e.to_string()))?;
+
+        Ok(RodioBackend {
+            _stream: stream,
+            stream_handle,
+            sinks: HashMap::new(),
+            pans: HashMap::new(),
+        })
+    }
+}
+
+impl AudioBackend for RodioBackend {
+    fn start(
+        &mut self,
+        handle: SoundHandle,
+        request: &SoundRequest,
+        volume: f32,
+        paused: bool,
+    ) -> Result<(), AudioError> {
+        let sink = Sink::try_new(&self.stream_handle)
+            .map_err(|e| AudioError::NoDevice(e.to_string()))?;
+
+        sink.set_volume(volume);
+        if paused {
+            sink.pause();
+        }
+
+        match &request.source {
+            SoundSource::Buffered(data) => {
+                let source = BufferedSound::new(data.clone(), request.clip, request.looped);
+                if request.spatial {
+                    let pan = Arc::new(AtomicU32::new(0.0_f32.to_bits()));
+                    sink.append(Panned::new(source, pan.clone()));
+                    self.pans.insert(handle, pan);
+                } else if let Some(reverb) = request.reverb {
+                    sink.append(source.reverb(reverb.delay, reverb.amount));
+                } else {
+                    sink.append(source);
+                }
+            }
+            SoundSource::Stream(path) => {
+                let file = File::open(path).map_err(|error| AudioError::Io {
+                    path: path.clone(),
+                    error,
+                })?;
+                let source = Decoder::new(BufReader::new(file)).map_err(|e| AudioError::Decode {
+                    path: path.clone(),
+                    reason: e.to_string(),
+                })?;
+
+                match (request.looped, request.clip) {
+                    (true, _) => sink.append(source.repeat_infinite()),
+                    (false, Some((start, length))) => {
+                        sink.append(source.skip_duration(start).take_duration(length))
+                    }
+                    (false, None) => sink.append(source),
+                }
+            }
+        }
+
+        self.sinks.insert(handle, sink);
+        Ok(())
+    }
+
+    fn stop(&mut self, handle: SoundHandle) {
+        self.pans.remove(&handle);
+        if let Some(sink) = self.sinks.remove(&handle) {
+            sink.stop();
+        }
+    }
+
+    fn set_volume(&mut self, handle: SoundHandle, volume: f32) {
+        if let Some(sink) = self.sinks.get(&handle) {
+            sink.set_volume(volume);
+        }
+    }
+
+    fn pause(&mut self, handle: SoundHandle) {
+        if let Some(sink) = self.sinks.get(&handle) {
+            sink.pause();
+        }
+    }
+
+    fn resume(&mut self, handle: SoundHandle) {
+        if let Some(sink) = self.sinks.get(&handle) {
+            sink.play();
+        }
+    }
+
+    fn set_pan(&mut self, handle: SoundHandle, pan: f32) {
+        if let Some(shared) = self.pans.get(&handle) {
+            shared.store(pan.clamp(-1.0, 1.0).to_bits(), Ordering::Relaxed);
+        }
+    }
+
+    // Los sinks que ya terminaron se liberan aquí mismo
+    fn is_finished(&mut self, handle: SoundHandle) -> bool {
+        let finished = self.sinks.get(&handle).is_none_or(|sink| sink.empty());
+        if finished {
+            self.sinks.remove(&handle);
+            self.pans.remove(&handle);
+        }
+        finished
+    }
+}
+
+// Convierte cualquier fuente a estéreo y reparte el volumen entre los dos
+// lados según un paneo que se puede cambiar mientras suena
+struct Panned<S> {
+    inner: S,
+    pan: Arc<AtomicU32>,
+    pending_right: Option<i16>,
+}
+
+impl<S: Source<Item = i16>> Panned<S> {
+    fn new(inner: S, pan: Arc<AtomicU32>) -> Self {
+        Panned {
+            inner,
+            pan,
+            pending_right: None,
+        }
+    }
+
+    fn gains(&self) -> (f32, f32) {
+        let pan = f32::from_bits(self.pan.load(Ordering::Relaxed));
+        ((1.0 - pan).min(1.0), (1.0 + pan).min(1.0))
+    }
+}
+
+impl<S: Source<Item = i16>> Iterator for Panned<S> {
+    type Item = i16;
+
+    fn next(&mut self) -> Option<i16> {
+        if let Some(right) = self.pending_right.take() {
+            return Some(right);
+        }
+
+        let (left_gain, right_gain) = self.gains();
+        let left = self.inner.next()? as f32;
+        // En fuentes mono la misma muestra alimenta ambos lados
+        let right = if self.inner.channels() >= 2 {
+            self.inner.next().unwrap_or(0) as f32
+        } else {
+            left
+        };
+
+        self.pending_right = Some((right * right_gain) as i16);
+        Some((left * left_gain) as i16)
+    }
+}
+
+impl<S: Source<Item = i16>> Source for Panned<S> {
+    fn current_frame_len(&self) -> Option<usize> {
+        None
+    }
+
+    fn channels(&self) -> u16 {
+        2
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.inner.sample_rate()
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        self.inner.total_duration()
+    }
+}
+
+// Backend mudo para equipos sin dispositivo de audio: todo sonido termina
+// en cuanto empieza
+pub struct SilentBackend;
+
+impl AudioBackend for SilentBackend {
+    fn start(
+        &mut self,
+        _handle: SoundHandle,
+        _request: &SoundRequest,
+        _volume: f32,
+        _paused: bool,
+    ) -> Result<(), AudioError> {
+        Ok(())
+    }
+
+    fn stop(&mut self, _handle: SoundHandle) {}
+    fn set_volume(&mut self, _handle: SoundHandle, _volume: f32) {}
+    fn pause(&mut self, _handle: SoundHandle) {}
+    fn resume(&mut self, _handle: SoundHandle) {}
+    fn set_pan(&mut self, _handle: SoundHandle, _pan: f32) {}
+
+    fn is_finished(&mut self, _handle: SoundHandle) -> bool {
+        true
+    }
+}
+
+#[derive(Clone)]
+pub enum AudioEventKind {
+    Started(SoundRequest),
+    Stopped,
+    VolumeChanged(f32),
+    PanChanged(f32),
+    Paused,
+    Resumed,
+}
+
+#[derive(Clone)]
+pub struct AudioEvent {
+    pub at: Duration, // Tiempo desde que se creó el backend
+    pub handle: SoundHandle,
+    pub kind: AudioEventKind,
+}
+
+impl fmt::Display for AudioEvent {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:>8.3}s {:?} ", self.at.as_secs_f32(), self.handle)?;
+        match &self.kind {
+            AudioEventKind::Started(request) => {
+                write!(f, "started {} on {:?}", request.name, request.channel)?;
+                if request.looped {
+                    write!(f, " (looped)")?;
+                }
+                if let Some(reverb) = request.reverb {
+                    write!(
+                        f,
+                        " reverb {}ms {:.2}",
+                        reverb.delay.as_millis(),
+                        reverb.amount
+                    )?;
+                }
+                Ok(())
+            }
+            AudioEventKind::Stopped => write!(f, "stopped"),
+            AudioEventKind::VolumeChanged(volume) => write!(f, "volume {:.2}", volume),
+            AudioEventKind::PanChanged(pan) => write!(f, "pan {:.2}", pan),
+            AudioEventKind::Paused => write!(f, "paused"),
+            AudioEventKind::Resumed => write!(f, "resumed"),
+        }
+    }
+}
+
+// Registro compartido de eventos, se puede consultar mientras el backend
+// sigue en uso por el AudioManager
+#[derive(Clone, Default)]
+pub struct AudioLog {
+    events: Rc<RefCell<Vec<AudioEvent>>>,
+}
+
+impl AudioLog {
+    pub fn events(&self) -> Vec<AudioEvent> {
+        self.events.borrow().clone()
+    }
+}
+
+// Backend que no produce sonido pero anota qué se reprodujo y cuándo. Los
+// sonidos en bucle siguen "sonando" hasta que se detienen
+pub struct RecordingBackend {
+    started: Instant,
+    log: AudioLog,
+    looping: HashSet<SoundHandle>,
+}
+
+impl RecordingBackend {
+    pub fn new() -> Self {
+        RecordingBackend {
+            started: Instant::now(),
+            log: AudioLog::default(),
+            looping: HashSet::new(),
+        }
+    }
+
+    pub fn log(&self) -> AudioLog {
+        self.log.clone()
+    }
+
+    fn record(&mut self, handle: SoundHandle, kind: AudioEventKind) {
+        self.log.events.borrow_mut().push(AudioEvent {
+            at: self.started.elapsed(),
+            handle,
+            kind,
+        });
+    }
+}
+
+impl AudioBackend for RecordingBackend {
+    fn start(
+        &mut self,
+        handle: SoundHandle,
+        request: &SoundRequest,
+        _volume: f32,
+        _paused: bool,
+    ) -> Result<(), AudioError> {
+        if request.looped {
+            self.looping.insert(handle);
+        }
+        self.record(handle, AudioEventKind::Started(request.clone()));
+        Ok(())
+    }
+
+    fn stop(&mut self, handle: SoundHandle) {
+        self.looping.remove(&handle);
+        self.record(handle, AudioEventKind::Stopped);
+    }
+
+    fn set_volume(&mut self, handle: SoundHandle, volume: f32) {
+        self.record(handle, AudioEventKind::VolumeChanged(volume));
+    }
+
+    fn pause(&mut self, handle: SoundHandle) {
+        self.record(handle, AudioEventKind::Paused);
+    }
+
+    fn resume(&mut self, handle: SoundHandle) {
+        self.record(handle, AudioEventKind::Resumed);
+    }
+
+    fn set_pan(&mut self, handle: SoundHandle, pan: f32) {
+        self.record(handle, AudioEventKind::PanChanged(pan));
+    }
+
+    fn is_finished(&mut self, handle: SoundHandle) -> bool {
+        !self.looping.contains(&handle)
+    }
+}
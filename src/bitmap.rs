@@ -0,0 +1,125 @@
+use std::path::Path;
+
+// Imagen ya decodificada en memoria; cada píxel es 0xAARRGGBB
+pub struct Image {
+    pub width: usize,
+    pub height: usize,
+    pixels: Vec<u32>,
+}
+
+// Rectángulo dentro de una imagen, por ejemplo un cuadro de una hoja de
+// sprites
+#[derive(Clone, Copy, Debug)]
+pub struct Rect {
+    pub x: usize,
+    pub y: usize,
+    pub width: usize,
+    pub height: usize,
+}
+
+impl Image {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let decoded = image::open(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        let rgba = decoded.to_rgba8();
+        let pixels = rgba
+            .pixels()
+            .map(|p| (p[3] as u32) << 24 | (p[0] as u32) << 16 | (p[1] as u32) << 8 | p[2] as u32)
+            .collect();
+        Ok(Image {
+            width: rgba.width() as usize,
+            height: rgba.height() as usize,
+            pixels,
+        })
+    }
+
+    pub fn pixel(&self, x: usize, y: usize) -> u32 {
+        self.pixels[y * self.width + x]
+    }
+
+    pub fn bounds(&self) -> Rect {
+        Rect { x: 0, y: 0, width: self.width, height: self.height }
+    }
+
+    // Crea una imagen nueva tomando cada píxel de `source(x, y)`
+    fn remap(width: usize, height: usize, source: impl Fn(usize, usize) -> u32) -> Image {
+        let pixels = (0..height)
+            .flat_map(|y| (0..width).map(move |x| (x, y)))
+            .map(|(x, y)| source(x, y))
+            .collect();
+        Image { width, height, pixels }
+    }
+
+    pub fn flipped(&self, horizontal: bool, vertical: bool) -> Image {
+        Image::remap(self.width, self.height, |x, y| {
+            let x = if horizontal { self.width - 1 - x } else { x };
+            let y = if vertical { self.height - 1 - y } else { y };
+            self.pixel(x, y)
+        })
+    }
+
+    // Gira de a cuartos de vuelta en sentido horario
+    pub fn rotated(&self, quarter_turns: u32) -> Image {
+        let (w, h) = (self.width, self.height);
+        match quarter_turns % 4 {
+            1 => Image::remap(h, w, |x, y| self.pixel(y, h - 1 - x)),
+            2 => self.flipped(true, true),
+            3 => Image::remap(h, w, |x, y| self.pixel(w - 1 - y, x)),
+            _ => Image::remap(w, h, |x, y| self.pixel(x, y)),
+        }
+    }
+
+    // Multiplica cada canal por el del color; blanco deja la imagen igual
+    pub fn tinted(&self, color: u32) -> Image {
+        let channel = |pixel: u32, shift: u32| (((pixel >> shift) & 0xFF) * ((color >> shift) & 0xFF) / 255) << shift;
+        Image::remap(self.width, self.height, |x, y| {
+            let pixel = self.pixel(x, y);
+            pixel & 0xFF000000 | channel(pixel, 16) | channel(pixel, 8) | channel(pixel, 0)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::framebuffer::Framebuffer;
+
+    // Colores del cuadro sin el canal alpha
+    fn colors(fb: &Framebuffer, range: std::ops::Range<usize>) -> Vec<u32> {
+        fb.buffer[range].iter().map(|pixel| pixel & 0xFFFFFF).collect()
+    }
+
+    // Imagen de 4x2 con cada píxel opaco de un color distinto
+    fn numbered() -> Image {
+        Image::remap(4, 2, |x, y| 0xFF000000 | (y * 4 + x + 1) as u32)
+    }
+
+    #[test]
+    fn region_past_the_edge_draws_only_what_exists() {
+        let mut fb = Framebuffer::new(8, 4);
+        let source = Rect { x: 2, y: 1, width: 4, height: 3 };
+        fb.blit_region(&numbered(), source, 0, 0, 4, 3);
+
+        // Solo la fila 1, columnas 2 y 3 de la imagen; lo demás queda igual
+        assert_eq!(colors(&fb, 0..4), [7, 8, 0, 0]);
+        assert!(colors(&fb, 8..32).iter().all(|&pixel| pixel == 0));
+    }
+
+    #[test]
+    fn region_outside_the_image_draws_nothing() {
+        let mut fb = Framebuffer::new(8, 4);
+        let source = Rect { x: 4, y: 0, width: 2, height: 2 };
+        fb.blit_region(&numbered(), source, 0, 0, 8, 4);
+        assert!(colors(&fb, 0..32).iter().all(|&pixel| pixel == 0));
+    }
+
+    #[test]
+    fn scaled_region_past_the_edge_keeps_its_scale() {
+        let mut fb = Framebuffer::new(8, 4);
+        let source = Rect { x: 3, y: 0, width: 2, height: 1 };
+        fb.blit_region(&numbered(), source, 0, 0, 4, 2);
+
+        // Cada píxel de la imagen ocupa 2x2; la mitad fuera no se dibuja
+        assert_eq!(colors(&fb, 0..4), [4, 4, 0, 0]);
+        assert_eq!(colors(&fb, 8..12), [4, 4, 0, 0]);
+    }
+}
@@ -1,32 +1,63 @@
-use crate::framebuffer::Framebuffer;
+use crate::bitmap::Image;
+use crate::maze::{is_crawlspace, is_walkable};
 use crate::player::Player;
-use image::{DynamicImage, GenericImageView};
+use nalgebra_glm::Vec2;
+use std::collections::HashMap;
+use std::path::Path;
 
 pub struct Intersect {
     pub distance: f32,
     pub impact: char,
     pub tex_coord: f32, // Add this to track where on the wall the ray hit
+    pub crawl: Option<CrawlSpan>, // Primer tramo de techo bajo que cruza el rayo
 }
 
-pub fn load_textures() -> (DynamicImage, DynamicImage, DynamicImage, DynamicImage) {
-    let texture_plus = image::open("./src/img/TECH_4E.PNG").unwrap();
-    let texture_minus = image::open("./src/img/TECH_1E.PNG").unwrap();
-    let texture_pipe = image::open("./src/img/TECH_3B.PNG").unwrap();
-    let texture_g = image::open("./src/img/TECH_4F.PNG").unwrap();
-    (texture_plus, texture_minus, texture_pipe, texture_g)
+// Tramo del rayo que pasa por celdas de techo bajo ('=')
+pub struct CrawlSpan {
+    pub start: f32,
+    pub end: f32,
+    pub tex_coord: f32,
 }
 
-pub fn cast_ray(
-    framebuffer: &mut Framebuffer,
-    maze: &Vec<Vec<char>>,
-    player: &Player,
-    a: f32,
-    block_size: usize,
-    draw_line: bool,
-    is2d: bool,
-) -> Intersect {
+fn tex_coord_at(x: usize, y: usize, block_size: usize) -> f32 {
+    if x.is_multiple_of(block_size) {
+        // Golpe vertical
+        (y % block_size) as f32 / block_size as f32
+    } else if y.is_multiple_of(block_size) {
+        // Golpe horizontal
+        (x % block_size) as f32 / block_size as f32
+    } else {
+        // Determinar si es más cercano a un golpe vertical u horizontal
+        let x_mod = (x % block_size) as f32 / block_size as f32;
+        let y_mod = (y % block_size) as f32 / block_size as f32;
+        if x_mod > y_mod {
+            y_mod
+        } else {
+            x_mod
+        }
+    }
+}
+
+// Textura de cada tipo de pared
+const WALL_TEXTURES: [(char, &str); 4] = [
+    ('+', "./src/img/TECH_4E.PNG"),
+    ('-', "./src/img/TECH_1E.PNG"),
+    ('|', "./src/img/TECH_3B.PNG"),
+    ('g', "./src/img/TECH_4F.PNG"),
+];
+
+// Se cargan una sola vez al crear el juego
+pub fn load_textures() -> Result<HashMap<char, Image>, String> {
+    WALL_TEXTURES
+        .iter()
+        .map(|&(cell, path)| Ok((cell, Image::load(Path::new(path))?)))
+        .collect()
+}
+
+pub fn cast_ray(maze: &[Vec<char>], player: &Player, a: f32, block_size: usize, is2d: bool) -> Intersect {
     let mut d = 0.0;
-    framebuffer.set_current_color(0xFFFFFF);
+    let mut crawl: Option<CrawlSpan> = None;
+    let mut in_crawl = false;
 
     loop {
         let cos = d * a.cos();
@@ -51,32 +82,29 @@ pub fn cast_ray(
             break;
         }
 
-        if draw_line {
-            framebuffer.point(x, y);
-        }
-
-        if maze[j][i] != ' ' && maze[j][i] != 's' && maze[j][i] != 'g' {
-            let (hit_vertical, tex_coord) = if (x % block_size) == 0 {
-                // Golpe vertical
-                (true, (y % block_size) as f32 / block_size as f32)
-            } else if (y % block_size) == 0 {
-                // Golpe horizontal
-                (false, (x % block_size) as f32 / block_size as f32)
-            } else {
-                // Determinar si es más cercano a un golpe vertical u horizontal
-                let x_mod = (x % block_size) as f32 / block_size as f32;
-                let y_mod = (y % block_size) as f32 / block_size as f32;
-                if x_mod > y_mod {
-                    (true, y_mod)
-                } else {
-                    (false, x_mod)
+        if is_crawlspace(maze[j][i]) {
+            match crawl.as_mut() {
+                None => {
+                    crawl = Some(CrawlSpan {
+                        start: d,
+                        end: d,
+                        tex_coord: tex_coord_at(x, y, block_size),
+                    });
+                    in_crawl = true;
                 }
-            };
+                Some(span) if in_crawl => span.end = d,
+                _ => {}
+            }
+        } else {
+            in_crawl = false;
+        }
 
+        if !is_walkable(maze[j][i]) {
             return Intersect {
                 distance: d,
                 impact: maze[j][i],
-                tex_coord,
+                tex_coord: tex_coord_at(x, y, block_size),
+                crawl,
             };
         }
 
@@ -87,5 +115,121 @@ pub fn cast_ray(
         distance: d,
         impact: ' ',
         tex_coord: 0.0,
+        crawl,
+    }
+}
+
+// Marca como vistas las celdas que cruza un rayo hasta la pared que golpeó,
+// incluida esa pared
+pub fn reveal_ray(explored: &mut [Vec<bool>], from: Vec2, a: f32, distance: f32, block_size: usize) {
+    let direction = Vec2::new(a.cos(), a.sin());
+    let mut d = 0.0;
+
+    while d <= distance + 1.0 {
+        let point = from + direction * d;
+        if point.x >= 0.0 && point.y >= 0.0 {
+            let i = (point.x / block_size as f32) as usize;
+            let j = (point.y / block_size as f32) as usize;
+            if let Some(seen) = explored.get_mut(j).and_then(|row| row.get_mut(i)) {
+                *seen = true;
+            }
+        }
+        d += 2.0;
+    }
+}
+
+// Recorre la cuadrícula celda por celda (DDA) y revisa si alguna pared se
+// interpone entre dos puntos; así una esquina delgada no deja pasar el sonido
+pub fn is_occluded(maze: &[Vec<char>], from: Vec2, to: Vec2, block_size: usize) -> bool {
+    let size = block_size as f32;
+    let start = from / size;
+    let end = to / size;
+    let delta = end - start;
+
+    let mut i = start.x.floor() as i64;
+    let mut j = start.y.floor() as i64;
+
+    let step_i = if delta.x < 0.0 { -1 } else { 1 };
+    let step_j = if delta.y < 0.0 { -1 } else { 1 };
+
+    // Fracción del segmento que se avanza al cruzar una celda en cada eje
+    let span_x = if delta.x == 0.0 { f32::INFINITY } else { 1.0 / delta.x.abs() };
+    let span_y = if delta.y == 0.0 { f32::INFINITY } else { 1.0 / delta.y.abs() };
+
+    // Fracción del segmento hasta el primer borde de celda en cada eje
+    let mut next_x = if delta.x < 0.0 {
+        (start.x - i as f32) * span_x
+    } else {
+        (i as f32 + 1.0 - start.x) * span_x
+    };
+    let mut next_y = if delta.y < 0.0 {
+        (start.y - j as f32) * span_y
+    } else {
+        (j as f32 + 1.0 - start.y) * span_y
+    };
+
+    loop {
+        if !walkable_at(maze, i, j) {
+            return true;
+        }
+
+        // El segmento termina dentro de esta celda
+        if next_x.min(next_y) >= 1.0 {
+            return false;
+        }
+
+        if next_x < next_y {
+            i += step_i;
+            next_x += span_x;
+        } else if next_y < next_x {
+            j += step_j;
+            next_y += span_y;
+        } else {
+            // Pasa justo por una esquina: basta con que una de las dos
+            // celdas vecinas sea pared para tapar el sonido
+            if !walkable_at(maze, i + step_i, j) || !walkable_at(maze, i, j + step_j) {
+                return true;
+            }
+            i += step_i;
+            next_x += span_x;
+            j += step_j;
+            next_y += span_y;
+        }
+    }
+}
+
+fn walkable_at(maze: &[Vec<char>], i: i64, j: i64) -> bool {
+    let (Ok(i), Ok(j)) = (usize::try_from(i), usize::try_from(j)) else {
+        return false;
+    };
+    matches!(maze.get(j).and_then(|row| row.get(i)), Some(&cell) if is_walkable(cell))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn maze(rows: &[&str]) -> Vec<Vec<char>> {
+        rows.iter().map(|row| row.chars().collect()).collect()
+    }
+
+    #[test]
+    fn open_corridor_is_not_occluded() {
+        let maze = maze(&["+---+", "|   |", "+---+"]);
+        assert!(!is_occluded(&maze, Vec2::new(15.0, 15.0), Vec2::new(35.0, 15.0), 10));
+    }
+
+    #[test]
+    fn wall_between_points_occludes() {
+        let maze = maze(&["+---+", "| | |", "+---+"]);
+        assert!(is_occluded(&maze, Vec2::new(15.0, 15.0), Vec2::new(35.0, 15.0), 10));
+    }
+
+    #[test]
+    fn diagonal_through_wall_corner_occludes() {
+        // Las dos celdas libres solo se tocan por la esquina
+        let maze = maze(&["+--+", "| +|", "|+ |", "+--+"]);
+        assert!(is_occluded(&maze, Vec2::new(15.0, 15.0), Vec2::new(25.0, 25.0), 10));
+        assert!(is_occluded(&maze, Vec2::new(12.0, 18.0), Vec2::new(28.0, 22.0), 10));
     }
 }
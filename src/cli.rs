@@ -0,0 +1,113 @@
+use crate::settings::MAX_WINDOW_SCALE;
+use std::path::PathBuf;
+
+pub const USAGE: &str = "usage: sr_02_line [options]
+
+options:
+  --maze <file>         play this maze file, skipping the title screen
+  --seed <n>            play a maze generated from this seed
+  --windowed            use a fixed-size window instead of fitting the screen
+  --scale <1-4>         window size as a multiple of the render size (implies --windowed)
+  --mute                start with the sound muted
+  --config <file>       read and save settings in this file
+  --record <file>       write the input of every frame to this file
+  --replay <file>       play back input recorded with --record
+  --headless            run without a window, sound or frame limit
+  --frames <n>          stop after this many frames
+  --set <section.key=value>
+                        override a config value (can be repeated)
+  -h, --help            show this help";
+
+const DEFAULT_WINDOWED_SCALE: usize = 2;
+
+// Opciones de la línea de comandos
+#[derive(Default)]
+pub struct Options {
+    pub maze: Option<PathBuf>,
+    pub seed: Option<u64>,
+    pub window_scale: Option<usize>, // Solo con --windowed o --scale
+    pub mute: bool,
+    pub config: Option<PathBuf>,
+    pub record: Option<PathBuf>,
+    pub replay: Option<PathBuf>,
+    pub headless: bool,
+    pub frames: Option<usize>,
+    pub overrides: Vec<String>,
+}
+
+// Lo que pidió la línea de comandos: jugar o solo mostrar la ayuda
+pub enum Command {
+    Run(Options),
+    Help,
+}
+
+fn parse_count(option: &str, value: &str, min: u64, max: u64) -> Result<u64, String> {
+    match value.parse::<u64>() {
+        Ok(n) if (min..=max).contains(&n) => Ok(n),
+        _ => Err(format!("{} expects a number from {} to {}, found {}", option, min, max, value)),
+    }
+}
+
+pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Command, String> {
+    let mut options = Options::default();
+    let mut windowed = false;
+    let mut scale = None;
+    let mut args = args.into_iter();
+
+    while let Some(arg) = args.next() {
+        // Acepta tanto `--opcion valor` como `--opcion=valor`
+        let (name, inline) = match arg.split_once('=') {
+            Some((name, value)) if name.starts_with("--") => (name.to_string(), Some(value.to_string())),
+            _ => (arg.clone(), None),
+        };
+        let takes_value = matches!(
+            name.as_str(),
+            "--maze" | "--seed" | "--scale" | "--config" | "--record" | "--replay" | "--frames" | "--set"
+        );
+        let value = if takes_value {
+            match inline {
+                Some(value) => value,
+                None => args.next().ok_or_else(|| format!("{} needs a value", name))?,
+            }
+        } else if inline.is_some() {
+            return Err(format!("{} does not take a value", name));
+        } else {
+            String::new()
+        };
+
+        match name.as_str() {
+            "-h" | "--help" => return Ok(Command::Help),
+            "--maze" => options.maze = Some(PathBuf::from(value)),
+            "--seed" => options.seed = Some(parse_count(&name, &value, 0, u64::MAX)?),
+            "--windowed" => windowed = true,
+            "--scale" => scale = Some(parse_count(&name, &value, 1, MAX_WINDOW_SCALE as u64)? as usize),
+            "--mute" => options.mute = true,
+            "--config" => options.config = Some(PathBuf::from(value)),
+            "--record" => options.record = Some(PathBuf::from(value)),
+            "--replay" => options.replay = Some(PathBuf::from(value)),
+            "--headless" => options.headless = true,
+            "--frames" => options.frames = Some(parse_count(&name, &value, 1, u64::MAX)? as usize),
+            "--set" => options.overrides.push(value),
+            _ if arg.starts_with('-') => return Err(format!("unknown option {}", arg)),
+            _ => return Err(format!("unexpected argument {}", arg)),
+        }
+    }
+
+    if options.maze.is_some() && options.seed.is_some() {
+        return Err("--maze and --seed can't be used together".into());
+    }
+    if options.record.is_some() && options.replay.is_some() {
+        return Err("--record and --replay can't be used together".into());
+    }
+    if options.headless && options.frames.is_none() && options.replay.is_none() {
+        return Err("--headless needs --frames or --replay to know when to stop".into());
+    }
+    if options.headless && (windowed || scale.is_some()) {
+        return Err("--headless can't be used with --windowed or --scale".into());
+    }
+    if windowed || scale.is_some() {
+        options.window_scale = Some(scale.unwrap_or(DEFAULT_WINDOWED_SCALE));
+    }
+
+    Ok(Command::Run(options))
+}
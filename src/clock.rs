@@ -0,0 +1,71 @@
+use std::time::{Duration, Instant};
+
+// Paso fijo de simulación: la lógica del juego siempre avanza en incrementos
+// de `step` segundos, sin importar cuántos cuadros por segundo se dibujen.
+pub struct FixedTimestep {
+    pub step: f32,
+    accumulator: f32,
+    last_time: Instant,
+    max_frame_time: f32, // Evita la "espiral de la muerte" si un cuadro tarda demasiado
+}
+
+impl FixedTimestep {
+    pub fn new(updates_per_second: f32) -> Self {
+        FixedTimestep {
+            step: 1.0 / updates_per_second,
+            accumulator: 0.0,
+            last_time: Instant::now(),
+            max_frame_time: 0.25,
+        }
+    }
+
+    // Acumula el tiempo real transcurrido y devuelve cuántos pasos de
+    // simulación hay que ejecutar en este cuadro
+    pub fn advance(&mut self) -> usize {
+        let now = Instant::now();
+        let frame_time = (now - self.last_time).as_secs_f32().min(self.max_frame_time);
+        self.last_time = now;
+        self.accumulator += frame_time;
+
+        let mut steps = 0;
+        while self.accumulator >= self.step {
+            self.accumulator -= self.step;
+            steps += 1;
+        }
+        steps
+    }
+
+    // Descarta el tiempo acumulado (por ejemplo después de cargar un nivel)
+    pub fn reset(&mut self) {
+        self.accumulator = 0.0;
+        self.last_time = Instant::now();
+    }
+}
+
+// Limita opcionalmente los cuadros por segundo que se dibujan
+pub struct FrameLimiter {
+    target: Option<Duration>,
+    last_frame: Instant,
+}
+
+impl FrameLimiter {
+    pub fn new(max_fps: Option<u32>) -> Self {
+        FrameLimiter {
+            target: max_fps
+                .filter(|&fps| fps > 0)
+                .map(|fps| Duration::from_secs_f64(1.0 / fps as f64)),
+            last_frame: Instant::now(),
+        }
+    }
+
+    // Duerme solo el tiempo que falta para completar el cuadro
+    pub fn wait(&mut self) {
+        if let Some(target) = self.target {
+            let elapsed = self.last_frame.elapsed();
+            if elapsed < target {
+                std::thread::sleep(target - elapsed);
+            }
+        }
+        self.last_frame = Instant::now();
+    }
+}
@@ -0,0 +1,570 @@
+use crate::input::{is_bindable, key_from_name, key_name, Action};
+use crate::minimap::MinimapShape;
+use crate::player::{MAX_SENSITIVITY, MIN_SENSITIVITY};
+use crate::settings::{Settings, MAX_FOV, MAX_WINDOW_SCALE, MIN_FOV};
+use crate::sfx::Channel;
+use std::env;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+pub const DEFAULT_RENDER_WIDTH: usize = 390;
+pub const DEFAULT_RENDER_HEIGHT: usize = 270;
+// Tamaño mínimo del framebuffer en el que caben los menús y pantallas
+const MIN_RENDER_WIDTH: usize = 320;
+const MIN_RENDER_HEIGHT: usize = 200;
+
+// Ventana cuando la escala es "fit"; con una escala fija la ventana mide el
+// framebuffer multiplicado por ella
+pub struct WindowConfig {
+    pub width: usize,
+    pub height: usize,
+}
+
+pub struct RenderConfig {
+    pub width: usize, // Tamaño del framebuffer
+    pub height: usize,
+    pub block_size: usize, // Unidades del mundo por celda del laberinto
+    pub minimap_block_size: usize,
+    pub updates_per_second: f32,
+    pub max_fps: u32, // 0 = sin límite
+    pub background_color: u32,
+    pub ceiling_color: u32,
+    pub floor_color: u32,
+}
+
+pub struct PlayerConfig {
+    pub move_speed: f32, // Unidades por segundo
+    pub run_multiplier: f32,
+    pub radius: f32,
+}
+
+// Posición y balanceo del farol en pantalla, en píxeles medidos sobre un
+// framebuffer del tamaño por omisión; con otro tamaño el farol guarda la
+// distancia a la esquina de abajo a la derecha
+pub struct LanternConfig {
+    pub x: f32,
+    pub y: f32,
+    pub min_x: f32,
+    pub max_x: f32,
+    pub sway_speed: f32,
+    pub walk_sway_x: f32,
+    pub walk_bob_y: f32,
+    pub left_hand: bool, // Farol a la izquierda de la pantalla, reflejado
+    pub vignette: f32,   // Cuánto se oscurecen las esquinas de la vista; 0 = nada
+}
+
+// Todos los valores ajustables del juego. Las opciones que el jugador cambia
+// desde el menú viven en `settings` y se guardan en el mismo archivo
+pub struct Config {
+    pub window: WindowConfig,
+    pub render: RenderConfig,
+    pub player: PlayerConfig,
+    pub lantern: LanternConfig,
+    pub settings: Settings,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            window: WindowConfig {
+                width: 1080,
+                height: 720,
+            },
+            render: RenderConfig {
+                width: DEFAULT_RENDER_WIDTH,
+                height: DEFAULT_RENDER_HEIGHT,
+                block_size: 30,
+                minimap_block_size: 5,
+                updates_per_second: 60.0,
+                max_fps: 60,
+                background_color: 0xb69f66,
+                ceiling_color: 0x252423,
+                floor_color: 0x5b6567,
+            },
+            player: PlayerConfig {
+                move_speed: 150.0,
+                run_multiplier: 2.0,
+                radius: 6.0,
+            },
+            lantern: LanternConfig {
+                x: 260.0,
+                y: 190.0,
+                min_x: 255.0,
+                max_x: 270.0,
+                sway_speed: 50.0,
+                walk_sway_x: 6.0,
+                walk_bob_y: 3.0,
+                left_hand: false,
+                vignette: 0.35,
+            },
+            settings: Settings::default(),
+        }
+    }
+}
+
+// $XDG_CONFIG_HOME/maze_runner/config.toml, o ~/.config/... si no está
+// definida; sin ninguna de las dos se usa el directorio actual
+pub fn default_path() -> PathBuf {
+    let base = env::var_os("XDG_CONFIG_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")));
+
+    match base {
+        Some(dir) => dir.join("maze_runner").join("config.toml"),
+        None => PathBuf::from("config.toml"),
+    }
+}
+
+// Quita el comentario de una línea sin cortar dentro de un texto entre comillas
+fn strip_comment(line: &str) -> &str {
+    let mut quoted = false;
+    for (i, ch) in line.char_indices() {
+        match ch {
+            '"' => quoted = !quoted,
+            '#' if !quoted => return &line[..i],
+            _ => {}
+        }
+    }
+    line
+}
+
+// Nombre de la sección si la línea es un encabezado; se aceptan espacios
+// dentro de los corchetes, como en `[ audio ]`
+fn section_name(line: &str) -> Option<&str> {
+    strip_comment(line)
+        .trim()
+        .strip_prefix('[')
+        .and_then(|l| l.strip_suffix(']'))
+        .map(str::trim)
+}
+
+fn parse_bool(value: &str) -> Result<bool, String> {
+    match value {
+        "true" => Ok(true),
+        "false" => Ok(false),
+        _ => Err(format!("expected true or false, found {}", value)),
+    }
+}
+
+fn parse_number(value: &str, min: f32, max: f32) -> Result<f32, String> {
+    let number: f32 = value
+        .replace('_', "")
+        .parse()
+        .map_err(|_| format!("expected a number, found {}", value))?;
+    if !(min..=max).contains(&number) {
+        return Err(format!("{} is outside {}..{}", number, min, max));
+    }
+    Ok(number)
+}
+
+fn parse_integer(value: &str, min: usize, max: usize) -> Result<usize, String> {
+    let number: usize = value
+        .replace('_', "")
+        .parse()
+        .map_err(|_| format!("expected a whole number, found {}", value))?;
+    if !(min..=max).contains(&number) {
+        return Err(format!("{} is outside {}..{}", number, min, max));
+    }
+    Ok(number)
+}
+
+// Colores como enteros hexadecimales de TOML: 0xRRGGBB
+fn parse_color(value: &str) -> Result<u32, String> {
+    value
+        .strip_prefix("0x")
+        .and_then(|hex| u32::from_str_radix(&hex.replace('_', ""), 16).ok())
+        .filter(|&color| color <= 0xFFFFFF)
+        .ok_or_else(|| format!("expected a color like 0xRRGGBB, found {}", value))
+}
+
+fn parse_string(value: &str) -> Result<&str, String> {
+    value
+        .strip_prefix('"')
+        .and_then(|v| v.strip_suffix('"'))
+        .ok_or_else(|| format!("expected a quoted string, found {}", value))
+}
+
+// Nombre del volumen de cada canal en la sección [audio]
+fn volume_key(channel: Channel) -> &'static str {
+    match channel {
+        Channel::Music => "music_volume",
+        Channel::Sfx => "sfx_volume",
+        Channel::Ambience => "ambience_volume",
+        Channel::Ui => "ui_volume",
+    }
+}
+
+impl Config {
+    // Lee el archivo. Si no existe se usan los valores por defecto; las
+    // líneas con errores se reportan y se ignoran
+    pub fn load(path: &Path) -> Self {
+        let text = match fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Config::default(),
+            Err(e) => {
+                eprintln!("could not read {}: {}", path.display(), e);
+                return Config::default();
+            }
+        };
+
+        let mut config = Config::parse(&text, |number, e| {
+            eprintln!("{}:{}: {}", path.display(), number, e);
+        });
+        for problem in config.validate() {
+            eprintln!("{}: {}", path.display(), problem);
+        }
+        config
+    }
+
+    // Aplica cada línea sobre los valores por defecto; las que fallan se
+    // pasan a `report` con su número de línea
+    fn parse(text: &str, mut report: impl FnMut(usize, String)) -> Self {
+        let mut config = Config::default();
+        let mut section = String::new();
+        for (number, line) in text.lines().enumerate() {
+            let line = strip_comment(line).trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            if let Some(name) = section_name(line) {
+                section = name.to_string();
+                continue;
+            }
+
+            let result = match line.split_once('=') {
+                Some((key, value)) => config.set(&section, key.trim(), value.trim()),
+                None => Err(format!("expected key = value, found {}", line)),
+            };
+            if let Err(e) = result {
+                report(number + 1, e);
+            }
+        }
+        config
+    }
+
+    // Cambio desde la línea de comandos: `seccion.clave=valor`
+    pub fn apply_override(&mut self, assignment: &str) -> Result<(), String> {
+        let (name, value) = assignment
+            .split_once('=')
+            .ok_or_else(|| format!("expected section.key=value, found {}", assignment))?;
+        let (section, key) = name
+            .trim()
+            .split_once('.')
+            .ok_or_else(|| format!("expected section.key, found {}", name))?;
+
+        self.set(section, key, value.trim())?;
+        match self.validate().first() {
+            Some(problem) => Err(problem.clone()),
+            None => Ok(()),
+        }
+    }
+
+    fn set(&mut self, section: &str, key: &str, value: &str) -> Result<(), String> {
+        let settings = &mut self.settings;
+        match (section, key) {
+            ("window", "width") => self.window.width = parse_integer(value, 160, 7680)?,
+            ("window", "height") => self.window.height = parse_integer(value, 120, 4320)?,
+            ("window", "scale") => {
+                settings.window_scale = match parse_string(value) {
+                    Ok("fit") => None,
+                    _ => Some(parse_integer(value, 1, MAX_WINDOW_SCALE)?),
+                }
+            }
+
+            ("render", "width") => self.render.width = parse_integer(value, 160, 1920)?,
+            ("render", "height") => self.render.height = parse_integer(value, 120, 1080)?,
+            ("render", "block_size") => self.render.block_size = parse_integer(value, 4, 256)?,
+            ("render", "minimap_block_size") => {
+                self.render.minimap_block_size = parse_integer(value, 1, 32)?
+            }
+            ("render", "updates_per_second") => {
+                self.render.updates_per_second = parse_number(value, 10.0, 1000.0)?
+            }
+            ("render", "max_fps") => self.render.max_fps = parse_integer(value, 0, 1000)? as u32,
+            ("render", "background_color") => self.render.background_color = parse_color(value)?,
+            ("render", "ceiling_color") => self.render.ceiling_color = parse_color(value)?,
+            ("render", "floor_color") => self.render.floor_color = parse_color(value)?,
+
+            ("player", "move_speed") => self.player.move_speed = parse_number(value, 1.0, 2000.0)?,
+            ("player", "run_multiplier") => {
+                self.player.run_multiplier = parse_number(value, 1.0, 10.0)?
+            }
+            ("player", "radius") => self.player.radius = parse_number(value, 1.0, 14.0)?,
+            ("player", "fov") => settings.fov = parse_number(value, MIN_FOV, MAX_FOV)?,
+            ("player", "mouse_sensitivity") => {
+                settings.mouse_sensitivity = parse_number(value, MIN_SENSITIVITY, MAX_SENSITIVITY)?
+            }
+
+            ("lantern", "x") => self.lantern.x = parse_number(value, 0.0, 1920.0)?,
+            ("lantern", "y") => self.lantern.y = parse_number(value, 0.0, 1080.0)?,
+            ("lantern", "min_x") => self.lantern.min_x = parse_number(value, 0.0, 1920.0)?,
+            ("lantern", "max_x") => self.lantern.max_x = parse_number(value, 0.0, 1920.0)?,
+            ("lantern", "sway_speed") => self.lantern.sway_speed = parse_number(value, 0.0, 1000.0)?,
+            ("lantern", "walk_sway_x") => self.lantern.walk_sway_x = parse_number(value, 0.0, 100.0)?,
+            ("lantern", "walk_bob_y") => self.lantern.walk_bob_y = parse_number(value, 0.0, 100.0)?,
+            ("lantern", "left_hand") => self.lantern.left_hand = parse_bool(value)?,
+            ("lantern", "vignette") => self.lantern.vignette = parse_number(value, 0.0, 1.0)?,
+
+            ("audio", "master_volume") => settings.master_volume = parse_number(value, 0.0, 1.0)?,
+            ("audio", "shuffle_music") => settings.shuffle_music = parse_bool(value)?,
+            ("audio", key) => {
+                let channel = Channel::ALL
+                    .into_iter()
+                    .find(|&channel| volume_key(channel) == key)
+                    .ok_or_else(|| format!("unknown setting audio.{}", key))?;
+                settings.set_channel_volume(channel, parse_number(value, 0.0, 1.0)?);
+            }
+
+            ("effects", "head_bob") => settings.head_bob = parse_bool(value)?,
+            ("effects", "lantern_sway") => settings.lantern_sway = parse_bool(value)?,
+            ("effects", "footstep_sounds") => settings.footstep_sounds = parse_bool(value)?,
+
+            ("minimap", "rotate") => settings.minimap_rotate = parse_bool(value)?,
+            ("minimap", "shape") => {
+                let name = parse_string(value)?;
+                settings.minimap_shape = MinimapShape::from_name(name)
+                    .ok_or_else(|| format!("unknown shape {} (expected circle or square)", name))?;
+            }
+
+            ("controls", key) => {
+                let action = Action::ALL
+                    .into_iter()
+                    .find(|action| action.name() == key)
+                    .ok_or_else(|| format!("unknown action {}", key))?;
+                let name = parse_string(value)?;
+                let key = key_from_name(name)
+                    .filter(|&key| is_bindable(key))
+                    .ok_or_else(|| format!("key {} can't be bound", name))?;
+                settings.bindings.set(action, key);
+            }
+
+            (section, key) => return Err(format!("unknown setting {}.{}", section, key)),
+        }
+        Ok(())
+    }
+
+    // Revisa combinaciones que cada valor por separado no puede detectar y
+    // corrige las que no tienen sentido con su valor por defecto
+    pub fn validate(&mut self) -> Vec<String> {
+        let mut problems = Vec::new();
+        let defaults = Config::default();
+
+        if self.lantern.min_x > self.lantern.max_x {
+            problems.push(format!(
+                "lantern.min_x ({}) is greater than lantern.max_x ({})",
+                self.lantern.min_x, self.lantern.max_x
+            ));
+            self.lantern.min_x = defaults.lantern.min_x;
+            self.lantern.max_x = defaults.lantern.max_x;
+        }
+
+        if self.render.width < MIN_RENDER_WIDTH || self.render.height < MIN_RENDER_HEIGHT {
+            problems.push(format!(
+                "render size {}x{} is smaller than the minimum {}x{}",
+                self.render.width, self.render.height, MIN_RENDER_WIDTH, MIN_RENDER_HEIGHT
+            ));
+            self.render.width = defaults.render.width;
+            self.render.height = defaults.render.height;
+        }
+
+        // Con un radio de media celda el jugador no cabe por los pasillos
+        if self.player.radius * 2.0 >= self.render.block_size as f32 {
+            problems.push(format!(
+                "player.radius ({}) does not fit in render.block_size ({})",
+                self.player.radius, self.render.block_size
+            ));
+            self.player.radius = defaults.player.radius.min(self.render.block_size as f32 / 2.0 - 1.0);
+        }
+
+        if self.render.minimap_block_size > self.render.block_size {
+            problems.push(format!(
+                "render.minimap_block_size ({}) is larger than render.block_size ({})",
+                self.render.minimap_block_size, self.render.block_size
+            ));
+            self.render.minimap_block_size = defaults.render.minimap_block_size;
+        }
+
+        problems
+    }
+
+    // Guarda las opciones del menú reescribiendo solo las que cambiaron: los
+    // comentarios, las demás claves y los cambios pasados por la línea de
+    // comandos quedan como estaban. Sin archivo se escribe uno completo
+    pub fn save_settings(path: &Path, settings: &Settings) -> io::Result<()> {
+        let text = match fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {
+                let config = Config {
+                    settings: settings.clone(),
+                    ..Config::default()
+                };
+                return config.save(path);
+            }
+            Err(e) => return Err(e),
+        };
+        let saved = settings_entries(&Config::parse(&text, |_, _| {}).settings);
+        let changed: Vec<Entry> = settings_entries(settings)
+            .into_iter()
+            .filter(|entry| !saved.contains(entry))
+            .collect();
+        if changed.is_empty() {
+            return Ok(());
+        }
+        fs::write(path, update_entries(&text, &changed))
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, self.to_toml())
+    }
+
+    fn to_toml(&self) -> String {
+        let entries = self.entries().into_iter().chain(settings_entries(&self.settings));
+        let mut text = String::new();
+        for section in SECTIONS {
+            if !text.is_empty() {
+                text.push('\n');
+            }
+            text.push_str(&format!("[{}]\n", section));
+            for (_, key, value) in entries.clone().filter(|(s, _, _)| *s == section) {
+                text.push_str(&format!("{} = {}\n", key, value));
+            }
+        }
+        text
+    }
+
+    // Valores del archivo que no cambian desde el menú de ajustes
+    fn entries(&self) -> Vec<Entry> {
+        let (window, render, player, lantern) = (&self.window, &self.render, &self.player, &self.lantern);
+        vec![
+            ("window", "width".into(), window.width.to_string()),
+            ("window", "height".into(), window.height.to_string()),
+            ("render", "width".into(), render.width.to_string()),
+            ("render", "height".into(), render.height.to_string()),
+            ("render", "block_size".into(), render.block_size.to_string()),
+            ("render", "minimap_block_size".into(), render.minimap_block_size.to_string()),
+            ("render", "updates_per_second".into(), render.updates_per_second.to_string()),
+            ("render", "max_fps".into(), render.max_fps.to_string()),
+            ("render", "background_color".into(), format!("0x{:06x}", render.background_color)),
+            ("render", "ceiling_color".into(), format!("0x{:06x}", render.ceiling_color)),
+            ("render", "floor_color".into(), format!("0x{:06x}", render.floor_color)),
+            ("player", "move_speed".into(), player.move_speed.to_string()),
+            ("player", "run_multiplier".into(), player.run_multiplier.to_string()),
+            ("player", "radius".into(), player.radius.to_string()),
+            ("lantern", "x".into(), lantern.x.to_string()),
+            ("lantern", "y".into(), lantern.y.to_string()),
+            ("lantern", "min_x".into(), lantern.min_x.to_string()),
+            ("lantern", "max_x".into(), lantern.max_x.to_string()),
+            ("lantern", "sway_speed".into(), lantern.sway_speed.to_string()),
+            ("lantern", "walk_sway_x".into(), lantern.walk_sway_x.to_string()),
+            ("lantern", "walk_bob_y".into(), lantern.walk_bob_y.to_string()),
+            ("lantern", "left_hand".into(), lantern.left_hand.to_string()),
+            ("lantern", "vignette".into(), lantern.vignette.to_string()),
+        ]
+    }
+}
+
+// Sección, clave y valor ya escrito como va en el archivo
+type Entry = (&'static str, String, String);
+
+// Orden de las secciones al escribir el archivo completo
+const SECTIONS: [&str; 8] = ["window", "render", "player", "lantern", "audio", "effects", "minimap", "controls"];
+
+// Valores que se cambian desde el menú de ajustes
+fn settings_entries(settings: &Settings) -> Vec<Entry> {
+    let mut entries = vec![
+        (
+            "window",
+            "scale".into(),
+            match settings.window_scale {
+                Some(scale) => scale.to_string(),
+                None => "\"fit\"".into(),
+            },
+        ),
+        ("player", "fov".into(), format!("{:.1}", settings.fov)),
+        ("player", "mouse_sensitivity".into(), settings.mouse_sensitivity.to_string()),
+        ("audio", "master_volume".into(), format!("{:.2}", settings.master_volume)),
+    ];
+    for channel in Channel::ALL {
+        entries.push(("audio", volume_key(channel).into(), format!("{:.2}", settings.channel_volume(channel))));
+    }
+    entries.extend([
+        ("audio", "shuffle_music".into(), settings.shuffle_music.to_string()),
+        ("effects", "head_bob".into(), settings.head_bob.to_string()),
+        ("effects", "lantern_sway".into(), settings.lantern_sway.to_string()),
+        ("effects", "footstep_sounds".into(), settings.footstep_sounds.to_string()),
+        ("minimap", "rotate".into(), settings.minimap_rotate.to_string()),
+        ("minimap", "shape".into(), format!("\"{}\"", settings.minimap_shape.name())),
+    ]);
+    for action in Action::ALL {
+        let key = key_name(settings.bindings.key(action)).unwrap_or("?");
+        entries.push(("controls", action.name().into(), format!("\"{}\"", key)));
+    }
+    entries
+}
+
+// Reemplaza el valor de cada clave en las líneas donde ya aparece, dejando
+// el comentario que tuviera al final; las que faltan se agregan al final de su
+// sección, o en una sección nueva al final del archivo
+fn update_entries(text: &str, entries: &[Entry]) -> String {
+    let mut lines: Vec<String> = text.lines().map(str::to_string).collect();
+    let mut found = vec![false; entries.len()];
+
+    let mut section = String::new();
+    for line in &mut lines {
+        let content = strip_comment(line).trim_end();
+        let comment = &line[content.len()..];
+        let content = content.trim();
+        if let Some(name) = section_name(content) {
+            section = name.to_string();
+            continue;
+        }
+        let Some((key, _)) = content.split_once('=') else {
+            continue;
+        };
+        let matching = entries
+            .iter()
+            .position(|(s, k, _)| *s == section && k == key.trim());
+        if let Some(i) = matching {
+            let (_, key, value) = &entries[i];
+            *line = format!("{} = {}{}", key, value, comment.trim_end());
+            found[i] = true;
+        }
+    }
+
+    let missing = entries.iter().zip(found).filter(|(_, found)| !found).map(|(entry, _)| entry);
+    for (section, key, value) in missing {
+        let entry = format!("{} = {}", key, value);
+        let header = lines.iter().position(|line| section_name(line) == Some(section));
+        match header {
+            Some(header) => {
+                // Después de la última línea con contenido de la sección
+                let end = lines[header + 1..]
+                    .iter()
+                    .position(|line| section_name(line).is_some())
+                    .map_or(lines.len(), |i| header + 1 + i);
+                let last = (header..end)
+                    .rev()
+                    .find(|&i| !lines[i].trim().is_empty())
+                    .unwrap_or(header);
+                lines.insert(last + 1, entry);
+            }
+            None => {
+                if lines.last().is_some_and(|line| !line.trim().is_empty()) {
+                    lines.push(String::new());
+                }
+                lines.push(format!("[{}]", section));
+                lines.push(entry);
+            }
+        }
+    }
+
+    let mut text = lines.join("\n");
+    text.push('\n');
+    text
+}
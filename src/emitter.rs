@@ -0,0 +1,110 @@
+use crate::caster::is_occluded;
+use crate::player::Player;
+use crate::sfx::{AudioManager, Channel, SoundHandle};
+use nalgebra_glm::Vec2;
+use std::f32::consts::PI;
+
+const OCCLUDED_GAIN: f32 = 0.3; // Volumen relativo con paredes de por medio
+const MIN_CHANGE: f32 = 0.01; // Cambios menores de volumen o paneo no se envían
+
+// Fuente de sonido ubicada en el laberinto
+pub struct Emitter {
+    pub pos: Vec2,
+    pub sound: &'static str,
+    pub radius: f32, // Distancia a la que deja de escucharse
+    pub volume: f32,
+    handle: Option<SoundHandle>,
+    gain: f32, // Últimos valores enviados al audio
+    pan: f32,
+}
+
+impl Emitter {
+    pub fn new(pos: Vec2, sound: &'static str, radius: f32, volume: f32) -> Self {
+        Emitter {
+            pos,
+            sound,
+            radius,
+            volume,
+            handle: None,
+            gain: 0.0,
+            pan: 0.0,
+        }
+    }
+
+    pub fn start(&mut self, audio: &mut AudioManager) {
+        match audio.play_spatial(Channel::Ambience, self.sound) {
+            Ok(handle) => {
+                // Empieza en silencio hasta el primer update
+                audio.set_gain(handle, 0.0);
+                self.handle = Some(handle);
+                self.gain = 0.0;
+                self.pan = 0.0;
+            }
+            Err(e) => eprintln!("{}", e),
+        }
+    }
+
+    pub fn stop(&mut self, audio: &mut AudioManager) {
+        if let Some(handle) = self.handle.take() {
+            audio.stop(handle);
+        }
+    }
+
+    // Volumen según distancia y línea de vista; paneo según el ángulo
+    // respecto a la dirección en la que mira el jugador
+    pub fn update(
+        &mut self,
+        audio: &mut AudioManager,
+        player: &Player,
+        maze: &[Vec<char>],
+        block_size: usize,
+    ) {
+        let handle = match self.handle {
+            Some(handle) => handle,
+            None => return,
+        };
+
+        let offset = self.pos - player.pos;
+        let distance = offset.norm();
+
+        let falloff = (1.0 - distance / self.radius).clamp(0.0, 1.0);
+        let mut gain = self.volume * falloff * falloff;
+        if gain > 0.0 && is_occluded(maze, player.pos, self.pos, block_size) {
+            gain *= OCCLUDED_GAIN;
+        }
+        // Llegar a cero siempre se envía para que el sonido se apague del todo
+        if (gain - self.gain).abs() > MIN_CHANGE || (gain == 0.0 && self.gain != 0.0) {
+            audio.set_gain(handle, gain);
+            self.gain = gain;
+        }
+
+        // Ángulo relativo normalizado a [-PI, PI]; positivo es a la derecha
+        let mut relative = offset.y.atan2(offset.x) - player.a;
+        relative = (relative + PI).rem_euclid(2.0 * PI) - PI;
+        let pan = if distance < 1.0 { 0.0 } else { relative.sin() };
+        if (pan - self.pan).abs() > MIN_CHANGE {
+            audio.set_pan(handle, pan);
+            self.pan = pan;
+        }
+    }
+}
+
+// Crea los emisores a partir de las celdas del laberinto: la salida 'g'
+// zumba como una máquina y las celdas 'd' gotean
+pub fn find_emitters(maze: &[Vec<char>], block_size: usize) -> Vec<Emitter> {
+    let mut emitters = Vec::new();
+    let bs = block_size as f32;
+
+    for (row, row_data) in maze.iter().enumerate() {
+        for (col, &cell) in row_data.iter().enumerate() {
+            let center = Vec2::new((col as f32 + 0.5) * bs, (row as f32 + 0.5) * bs);
+            match cell {
+                'g' => emitters.push(Emitter::new(center, "hum", bs * 8.0, 0.9)),
+                'd' => emitters.push(Emitter::new(center, "drip", bs * 5.0, 0.7)),
+                _ => {}
+            }
+        }
+    }
+
+    emitters
+}
@@ -0,0 +1,182 @@
+// Fuente de 5x7 píxeles para todo el ASCII imprimible (del espacio a '~').
+// Cada carácter son 5 columnas de izquierda a derecha; el bit 0 de cada
+// columna es la fila de arriba
+const GLYPHS: [[u8; 5]; 95] = [
+    [0x00, 0x00, 0x00, 0x00, 0x00], // ' '
+    [0x00, 0x00, 0x5f, 0x00, 0x00], // !
+    [0x00, 0x07, 0x00, 0x07, 0x00], // "
+    [0x14, 0x7f, 0x14, 0x7f, 0x14], // #
+    [0x24, 0x2a, 0x7f, 0x2a, 0x12], // $
+    [0x23, 0x13, 0x08, 0x64, 0x62], // %
+    [0x36, 0x49, 0x55, 0x22, 0x50], // &
+    [0x00, 0x05, 0x03, 0x00, 0x00], // '
+    [0x00, 0x1c, 0x22, 0x41, 0x00], // (
+    [0x00, 0x41, 0x22, 0x1c, 0x00], // )
+    [0x14, 0x08, 0x3e, 0x08, 0x14], // *
+    [0x08, 0x08, 0x3e, 0x08, 0x08], // +
+    [0x00, 0x50, 0x30, 0x00, 0x00], // ,
+    [0x08, 0x08, 0x08, 0x08, 0x08], // -
+    [0x00, 0x60, 0x60, 0x00, 0x00], // .
+    [0x20, 0x10, 0x08, 0x04, 0x02], // /
+    [0x3e, 0x51, 0x49, 0x45, 0x3e], // 0
+    [0x00, 0x42, 0x7f, 0x40, 0x00], // 1
+    [0x42, 0x61, 0x51, 0x49, 0x46], // 2
+    [0x21, 0x41, 0x45, 0x4b, 0x31], // 3
+    [0x18, 0x14, 0x12, 0x7f, 0x10], // 4
+    [0x27, 0x45, 0x45, 0x45, 0x39], // 5
+    [0x3c, 0x4a, 0x49, 0x49, 0x30], // 6
+    [0x01, 0x71, 0x09, 0x05, 0x03], // 7
+    [0x36, 0x49, 0x49, 0x49, 0x36], // 8
+    [0x06, 0x49, 0x49, 0x29, 0x1e], // 9
+    [0x00, 0x36, 0x36, 0x00, 0x00], // :
+    [0x00, 0x56, 0x36, 0x00, 0x00], // ;
+    [0x08, 0x14, 0x22, 0x41, 0x00], // <
+    [0x14, 0x14, 0x14, 0x14, 0x14], // =
+    [0x00, 0x41, 0x22, 0x14, 0x08], // >
+    [0x02, 0x01, 0x51, 0x09, 0x06], // ?
+    [0x32, 0x49, 0x79, 0x41, 0x3e], // @
+    [0x7e, 0x11, 0x11, 0x11, 0x7e], // A
+    [0x7f, 0x49, 0x49, 0x49, 0x36], // B
+    [0x3e, 0x41, 0x41, 0x41, 0x22], // C
+    [0x7f, 0x41, 0x41, 0x22, 0x1c], // D
+    [0x7f, 0x49, 0x49, 0x49, 0x41], // E
+    [0x7f, 0x09, 0x09, 0x09, 0x01], // F
+    [0x3e, 0x41, 0x49, 0x49, 0x7a], // G
+    [0x7f, 0x08, 0x08, 0x08, 0x7f], // H
+    [0x00, 0x41, 0x7f, 0x41, 0x00], // I
+    [0x20, 0x40, 0x41, 0x3f, 0x01], // J
+    [0x7f, 0x08, 0x14, 0x22, 0x41], // K
+    [0x7f, 0x40, 0x40, 0x40, 0x40], // L
+    [0x7f, 0x02, 0x0c, 0x02, 0x7f], // M
+    [0x7f, 0x04, 0x08, 0x10, 0x7f], // N
+    [0x3e, 0x41, 0x41, 0x41, 0x3e], // O
+    [0x7f, 0x09, 0x09, 0x09, 0x06], // P
+    [0x3e, 0x41, 0x51, 0x21, 0x5e], // Q
+    [0x7f, 0x09, 0x19, 0x29, 0x46], // R
+    [0x46, 0x49, 0x49, 0x49, 0x31], // S
+    [0x01, 0x01, 0x7f, 0x01, 0x01], // T
+    [0x3f, 0x40, 0x40, 0x40, 0x3f], // U
+    [0x1f, 0x20, 0x40, 0x20, 0x1f], // V
+    [0x3f, 0x40, 0x38, 0x40, 0x3f], // W
+    [0x63, 0x14, 0x08, 0x14, 0x63], // X
+    [0x07, 0x08, 0x70, 0x08, 0x07], // Y
+    [0x61, 0x51, 0x49, 0x45, 0x43], // Z
+    [0x00, 0x7f, 0x41, 0x41, 0x00], // [
+    [0x02, 0x04, 0x08, 0x10, 0x20], // \
+    [0x00, 0x41, 0x41, 0x7f, 0x00], // ]
+    [0x04, 0x02, 0x01, 0x02, 0x04], // ^
+    [0x40, 0x40, 0x40, 0x40, 0x40], // _
+    [0x00, 0x01, 0x02, 0x04, 0x00], // `
+    [0x20, 0x54, 0x54, 0x54, 0x78], // a
+    [0x7f, 0x48, 0x44, 0x44, 0x38], // b
+    [0x38, 0x44, 0x44, 0x44, 0x20], // c
+    [0x38, 0x44, 0x44, 0x48, 0x7f], // d
+    [0x38, 0x54, 0x54, 0x54, 0x18], // e
+    [0x08, 0x7e, 0x09, 0x01, 0x02], // f
+    [0x0c, 0x52, 0x52, 0x52, 0x3e], // g
+    [0x7f, 0x08, 0x04, 0x04, 0x78], // h
+    [0x00, 0x44, 0x7d, 0x40, 0x00], // i
+    [0x20, 0x40, 0x44, 0x3d, 0x00], // j
+    [0x7f, 0x10, 0x28, 0x44, 0x00], // k
+    [0x00, 0x41, 0x7f, 0x40, 0x00], // l
+    [0x7c, 0x04, 0x18, 0x04, 0x78], // m
+    [0x7c, 0x08, 0x04, 0x04, 0x78], // n
+    [0x38, 0x44, 0x44, 0x44, 0x38], // o
+    [0x7c, 0x14, 0x14, 0x14, 0x08], // p
+    [0x08, 0x14, 0x14, 0x18, 0x7c], // q
+    [0x7c, 0x08, 0x04, 0x04, 0x08], // r
+    [0x48, 0x54, 0x54, 0x54, 0x20], // s
+    [0x04, 0x3f, 0x44, 0x40, 0x20], // t
+    [0x3c, 0x40, 0x40, 0x20, 0x7c], // u
+    [0x1c, 0x20, 0x40, 0x20, 0x1c], // v
+    [0x3c, 0x40, 0x30, 0x40, 0x3c], // w
+    [0x44, 0x28, 0x10, 0x28, 0x44], // x
+    [0x0c, 0x50, 0x50, 0x50, 0x3c], // y
+    [0x44, 0x64, 0x54, 0x4c, 0x44], // z
+    [0x00, 0x08, 0x36, 0x41, 0x00], // {
+    [0x00, 0x00, 0x7f, 0x00, 0x00], // |
+    [0x00, 0x41, 0x36, 0x08, 0x00], // }
+    [0x08, 0x04, 0x08, 0x10, 0x08], // ~
+];
+
+pub const GLYPH_WIDTH: usize = 5;
+pub const GLYPH_HEIGHT: usize = 7;
+const ADVANCE: usize = GLYPH_WIDTH + 1; // Ancho de cada carácter con su espacio
+const LINE_HEIGHT: usize = GLYPH_HEIGHT + 2;
+
+// Columnas de un carácter; lo que no es ASCII imprimible se dibuja como '?'
+pub fn glyph(ch: char) -> [u8; 5] {
+    let index = match ch {
+        ' '..='~' => ch as usize - ' ' as usize,
+        _ => '?' as usize - ' ' as usize,
+    };
+    GLYPHS[index]
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Align {
+    Left,   // `x` es el borde izquierdo
+    Center, // `x` es el centro
+    Right,  // `x` es el borde derecho
+}
+
+// Cómo se dibuja un texto
+#[derive(Clone, Copy, Debug)]
+pub struct TextStyle {
+    pub color: u32,
+    pub scale: usize,           // Cada píxel de la fuente ocupa scale x scale
+    pub align: Align,
+    pub wrap: Option<usize>,    // Ancho máximo en píxeles antes de partir la línea
+    pub shadow: Option<u32>,    // Color de la sombra, un píxel abajo a la derecha
+}
+
+impl Default for TextStyle {
+    fn default() -> Self {
+        TextStyle {
+            color: 0xffffff,
+            scale: 1,
+            align: Align::Left,
+            wrap: None,
+            shadow: None,
+        }
+    }
+}
+
+impl TextStyle {
+    pub fn line_height(&self) -> usize {
+        LINE_HEIGHT * self.scale
+    }
+}
+
+// Ancho en píxeles de una línea de texto
+pub fn text_width(text: &str, scale: usize) -> usize {
+    (text.chars().count() * ADVANCE).saturating_sub(1) * scale
+}
+
+// Parte el texto en líneas: en cada '\n' y, si hay ancho máximo, entre
+// palabras. Una palabra más ancha que el máximo queda sola en su línea
+pub fn layout(text: &str, style: &TextStyle) -> Vec<String> {
+    let mut lines = Vec::new();
+    for paragraph in text.split('\n') {
+        let Some(max_width) = style.wrap else {
+            lines.push(paragraph.to_string());
+            continue;
+        };
+
+        let mut line = String::new();
+        for word in paragraph.split_whitespace() {
+            let candidate = if line.is_empty() {
+                word.to_string()
+            } else {
+                format!("{} {}", line, word)
+            };
+            if text_width(&candidate, style.scale) > max_width && !line.is_empty() {
+                lines.push(std::mem::replace(&mut line, word.to_string()));
+            } else {
+                line = candidate;
+            }
+        }
+        lines.push(line);
+    }
+    lines
+}
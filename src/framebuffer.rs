@@ -1,24 +1,69 @@
-use image::GenericImageView;
-
-const FONT: [[u8; 5]; 10] = [
-    [0b01110, 0b10001, 0b10001, 0b10001, 0b01110], // 0
-    [0b00100, 0b01100, 0b00100, 0b00100, 0b01110], // 1
-    [0b01110, 0b10001, 0b00110, 0b01000, 0b11111], // 2
-    [0b11110, 0b00101, 0b01110, 0b00101, 0b11110], // 3
-    [0b00100, 0b01100, 0b10100, 0b11111, 0b00100], // 4
-    [0b11111, 0b10000, 0b11110, 0b00001, 0b11110], // 5
-    [0b01110, 0b10000, 0b11110, 0b10001, 0b01110], // 6
-    [0b11111, 0b00001, 0b00010, 0b00100, 0b01000], // 7
-    [0b01110, 0b10001, 0b01110, 0b10001, 0b01110], // 8
-    [0b01110, 0b10001, 0b01111, 0b00001, 0b01110], // 9
-];
+use crate::bitmap::{Image, Rect};
+use crate::font::{self, Align, TextStyle};
+use crate::layer::Layer;
 
+// Mezcla lineal canal por canal entre dos colores RGB
+fn blend(dst: u32, color: u32, alpha: f32) -> u32 {
+    let mix = |shift: u32| {
+        let d = ((dst >> shift) & 0xFF) as f32;
+        let c = ((color >> shift) & 0xFF) as f32;
+        ((d + (c - d) * alpha) as u32) << shift
+    };
+    mix(16) | mix(8) | mix(0)
+}
+
+// Cómo se combina lo que se dibuja con lo que ya hay en el cuadro
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum BlendMode {
+    Normal,   // Reemplaza lo de abajo
+    Add,      // Suma los canales: brillos y luces
+    Multiply, // Multiplica: sombras y viñetas
+    Screen,   // Lo opuesto a multiplicar: aclara sin quemar tan rápido
+}
+
+// Aplica el modo canal por canal y mezcla el resultado según alpha
+fn composite(dst: u32, color: u32, mode: BlendMode, alpha: f32) -> u32 {
+    let channel = |shift: u32| {
+        let d = (dst >> shift) & 0xFF;
+        let c = (color >> shift) & 0xFF;
+        let value = match mode {
+            BlendMode::Normal => c,
+            BlendMode::Add => (d + c).min(0xFF),
+            BlendMode::Multiply => d * c / 0xFF,
+            BlendMode::Screen => 0xFF - (0xFF - d) * (0xFF - c) / 0xFF,
+        };
+        value << shift
+    };
+    let source = channel(16) | channel(8) | channel(0);
+    if alpha >= 1.0 {
+        source
+    } else {
+        blend(dst, source, alpha)
+    }
+}
+
+// Dibuja sobre un píxel que puede ser transparente, como los de una capa;
+// sobre uno opaco equivale a mezclar según alpha
+fn over(dst: u32, color: u32, mode: BlendMode, alpha: f32) -> u32 {
+    let dst_alpha = (dst >> 24) as f32 / 255.0;
+    let out_alpha = alpha + dst_alpha * (1.0 - alpha);
+    if out_alpha <= 0.0 {
+        return 0;
+    }
+    let source = composite(dst, color, mode, 1.0);
+    ((out_alpha * 255.0).round() as u32) << 24 | blend(dst, source, alpha / out_alpha)
+}
+
+// Cada píxel es 0xAARRGGBB; el cuadro principal es siempre opaco y las
+// capas empiezan transparentes
 pub struct Framebuffer {
     pub width: usize,
     pub height: usize,
     pub buffer: Vec<u32>,
     background_color: u32,
     current_color: u32,
+    blend_mode: BlendMode,
+    opacity: f32, // Se aplica a todo lo que se dibuja
 }
 
 impl Framebuffer {
@@ -26,24 +71,83 @@ impl Framebuffer {
         Framebuffer {
             width,
             height,
-            buffer: vec![0; width * height],
+            buffer: vec![0xFF000000; width * height],
             background_color: 0x000000,
             current_color: 0xFFFFFF,
+            blend_mode: BlendMode::Normal,
+            opacity: 1.0,
         }
     }
 
     pub fn clear(&mut self) {
-        for pixel in self.buffer.iter_mut() {
-            *pixel = self.background_color;
+        let color = self.background_color | 0xFF000000;
+        self.buffer.fill(color);
+    }
+
+    pub fn clear_transparent(&mut self) {
+        self.buffer.fill(0);
+    }
+
+    // Mezcla todo el cuadro con un color; alpha 0 no cambia nada y 1 lo
+    // cubre por completo. Sirve para fundidos y para oscurecer el fondo
+    pub fn overlay(&mut self, color: u32, alpha: f32) {
+        self.shade_rect(0, 0, self.width, self.height, color, alpha);
+    }
+
+    // Igual que overlay pero solo dentro de un rectángulo, por ejemplo para
+    // el fondo de un panel de texto
+    pub fn shade_rect(&mut self, x: usize, y: usize, width: usize, height: usize, color: u32, alpha: f32) {
+        let alpha = alpha.clamp(0.0, 1.0);
+        if alpha <= 0.0 {
+            return;
+        }
+
+        let right = (x + width).min(self.width);
+        for row in y..(y + height).min(self.height) {
+            for index in row * self.width + x.min(right)..row * self.width + right {
+                self.put(index, color, alpha);
+            }
+        }
+    }
+
+    // Un píxel mezclado con lo que ya hay, para bordes suavizados
+    pub fn blend_point(&mut self, x: usize, y: usize, color: u32, alpha: f32) {
+        if x < self.width && y < self.height {
+            self.put(y * self.width + x, color, alpha);
         }
     }
 
     pub fn point(&mut self, x: usize, y: usize) {
         if x < self.width && y < self.height {
-            self.buffer[y * self.width + x] = self.current_color;
+            self.put(y * self.width + x, self.current_color, 1.0);
         }
     }
 
+    // Todo lo que se dibuja pasa por aquí para respetar el modo de mezcla y
+    // la opacidad
+    fn put(&mut self, index: usize, color: u32, alpha: f32) {
+        let alpha = (alpha * self.opacity).min(1.0);
+        if alpha <= 0.0 {
+            return;
+        }
+        let pixel = &mut self.buffer[index];
+        *pixel = if self.blend_mode == BlendMode::Normal && alpha >= 1.0 {
+            color | 0xFF000000
+        } else {
+            over(*pixel, color, self.blend_mode, alpha)
+        };
+    }
+
+    // Quedan activos hasta que se cambien; quien los cambia los devuelve a
+    // Normal y 1.0 al terminar
+    pub fn set_blend_mode(&mut self, mode: BlendMode) {
+        self.blend_mode = mode;
+    }
+
+    pub fn set_opacity(&mut self, opacity: f32) {
+        self.opacity = opacity.clamp(0.0, 1.0);
+    }
+
     pub fn set_background_color(&mut self, color: u32) {
         self.background_color = color;
     }
@@ -51,78 +155,119 @@ impl Framebuffer {
     pub fn set_current_color(&mut self, color: u32) {
         self.current_color = color;
     }
-    pub fn draw_floor_and_ceiling(&mut self, ceiling_color: u32, floor_color: u32) {
-        // Color para el techo
-        for y in 0..self.height / 2 {
-            for x in 0..self.width {
-                self.buffer[y * self.width + x] = ceiling_color;
-            }
+
+    pub fn current_color(&self) -> u32 {
+        self.current_color
+    }
+    pub fn draw_char(&mut self, x: usize, y: usize, ch: char) {
+        self.draw_glyph(x as isize, y as isize, ch, 1);
+    }
+
+    // Una línea con el color actual, sin escala ni sombra
+    pub fn draw_text(&mut self, x: usize, y: usize, text: &str) {
+        for (i, ch) in text.chars().enumerate() {
+            self.draw_char(x + i * (font::GLYPH_WIDTH + 1), y, ch);
         }
+    }
 
-        // Color para el piso
-        for y in self.height / 2..self.height {
-            for x in 0..self.width {
-                self.buffer[y * self.width + x] = floor_color;
+    // Texto con estilo: escala, color, alineación respecto de `x`, corte de
+    // líneas y sombra. Devuelve el alto en píxeles de lo que dibujó
+    pub fn draw_text_styled(&mut self, x: usize, y: usize, text: &str, style: &TextStyle) -> usize {
+        let scale = style.scale.max(1);
+        let lines = font::layout(text, style);
+        for (i, line) in lines.iter().enumerate() {
+            let width = font::text_width(line, scale) as isize;
+            let left = match style.align {
+                Align::Left => x as isize,
+                Align::Center => x as isize - width / 2,
+                Align::Right => x as isize - width,
+            };
+            let top = (y + i * style.line_height()) as isize;
+
+            // Primero la sombra para que el texto quede encima
+            let passes = style.shadow.map(|color| (color, scale as isize)).into_iter().chain([(style.color, 0)]);
+            for (color, offset) in passes {
+                self.set_current_color(color);
+                for (j, ch) in line.chars().enumerate() {
+                    let advance = (j * (font::GLYPH_WIDTH + 1) * scale) as isize;
+                    self.draw_glyph(left + advance + offset, top + offset, ch, scale);
+                }
             }
         }
+        lines.len() * style.line_height()
     }
 
-    pub fn draw_char(&mut self, x: usize, y: usize, ch: char) {
-        if let Some(digit) = ch.to_digit(10) {
-            let pattern = FONT[digit as usize];
-            for (row, bits) in pattern.iter().enumerate() {
-                for col in 0..5 {
-                    if bits & (1 << (4 - col)) != 0 {
-                        self.point(x + col, y + row);
+    // Puede empezar fuera del cuadro por arriba o a la izquierda; solo se
+    // dibuja lo que cae dentro
+    fn draw_glyph(&mut self, x: isize, y: isize, ch: char, scale: usize) {
+        for (col, bits) in font::glyph(ch).iter().enumerate() {
+            for row in 0..font::GLYPH_HEIGHT {
+                if bits & (1 << row) == 0 {
+                    continue;
+                }
+                for dy in 0..scale {
+                    for dx in 0..scale {
+                        let px = x + (col * scale + dx) as isize;
+                        let py = y + (row * scale + dy) as isize;
+                        if px >= 0 && py >= 0 {
+                            self.point(px as usize, py as usize);
+                        }
                     }
                 }
             }
         }
     }
 
-    pub fn draw_text(&mut self, x: usize, y: usize, text: &str) {
-        for (i, ch) in text.chars().enumerate() {
-            self.draw_char(x + i * 6, y, ch);
+    // Compone una capa encima con su posición, opacidad y modo de mezcla
+    pub fn draw_layer(&mut self, layer: &Layer) {
+        let source = &layer.framebuffer;
+        let mode = std::mem::replace(&mut self.blend_mode, layer.blend_mode);
+        let x_range = layer.x.max(0)..(layer.x + source.width as isize).min(self.width as isize);
+        let y_range = layer.y.max(0)..(layer.y + source.height as isize).min(self.height as isize);
+        for y in y_range {
+            for x in x_range.clone() {
+                let color = source.buffer[(y - layer.y) as usize * source.width + (x - layer.x) as usize];
+                let alpha = (color >> 24) as f32 / 255.0 * layer.opacity;
+                self.put(y as usize * self.width + x as usize, color, alpha);
+            }
         }
+        self.blend_mode = mode;
     }
 
-    pub fn draw_image(&mut self, path: &str, x_offset: usize, y_offset: usize) {
-        // Carga la imagen desde el path especificado
-        let img = image::open(path).unwrap();
-        let img = img.to_rgba8(); // Asegúrate de que la imagen esté en formato RGBA
-    
-        let (img_width, img_height) = img.dimensions();
-    
-        // Recorre los píxeles de la imagen y cópialos en el framebuffer
-        for y in 0..img_height {
-            for x in 0..img_width {
-                if x as usize + x_offset < self.width && y as usize + y_offset < self.height {
-                    let pixel = img.get_pixel(x, y);
-                    let alpha = pixel[3] as f32 / 255.0; // Componente Alfa (transparencia)
-    
-                    // Si el píxel no es completamente transparente
-                    if alpha > 0.0 {
-                        let background_color = self.buffer[(y as usize + y_offset) * self.width + (x as usize + x_offset)];
-    
-                        // Extrae los componentes RGB del color de fondo
-                        let bg_r = ((background_color >> 16) & 0xFF) as f32;
-                        let bg_g = ((background_color >> 8) & 0xFF) as f32;
-                        let bg_b = (background_color & 0xFF) as f32;
-    
-                        // Mezcla los colores usando el valor alfa
-                        let r = ((pixel[0] as f32 * alpha) + (bg_r * (1.0 - alpha))) as u32;
-                        let g = ((pixel[1] as f32 * alpha) + (bg_g * (1.0 - alpha))) as u32;
-                        let b = ((pixel[2] as f32 * alpha) + (bg_b * (1.0 - alpha))) as u32;
-    
-                        // Combina los componentes y asigna el color resultante al framebuffer
-                        let rgba = (r << 16) | (g << 8) | b;
-                        self.buffer[(y as usize + y_offset) * self.width + (x as usize + x_offset)] = rgba;
-                    }
-                }
+    // Copia la imagen en su tamaño; puede quedar en parte fuera del cuadro
+    pub fn blit(&mut self, image: &Image, x: isize, y: isize) {
+        self.blit_region(image, image.bounds(), x, y, image.width, image.height);
+    }
+
+    // Estira la imagen completa al tamaño pedido
+    pub fn blit_scaled(&mut self, image: &Image, x: isize, y: isize, width: usize, height: usize) {
+        self.blit_region(image, image.bounds(), x, y, width, height);
+    }
+
+    // Copia la parte `source` de la imagen en un rectángulo del cuadro,
+    // escalando por vecino más cercano. Se recorta por los cuatro bordes y
+    // se respeta la transparencia. Si `source` se sale de la imagen solo se
+    // dibuja la parte que sí existe, con la misma escala
+    pub fn blit_region(&mut self, image: &Image, source: Rect, x: isize, y: isize, width: usize, height: usize) {
+        let source_width = image.width.saturating_sub(source.x).min(source.width);
+        let source_height = image.height.saturating_sub(source.y).min(source.height);
+        if width == 0 || height == 0 || source_width == 0 || source_height == 0 {
+            return;
+        }
+        // Columnas y filas del destino que caen dentro de la imagen
+        let visible_width = (source_width * width).div_ceil(source.width);
+        let visible_height = (source_height * height).div_ceil(source.height);
+
+        let x_range = x.max(0)..(x + visible_width as isize).min(self.width as isize);
+        let y_range = y.max(0)..(y + visible_height as isize).min(self.height as isize);
+
+        for py in y_range {
+            let sy = source.y + (py - y) as usize * source.height / height;
+            for px in x_range.clone() {
+                let sx = source.x + (px - x) as usize * source.width / width;
+                let color = image.pixel(sx, sy);
+                self.put(py as usize * self.width + px as usize, color, (color >> 24) as f32 / 255.0);
             }
         }
     }
-    
-    
-    
 }
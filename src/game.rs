@@ -1,240 +1,819 @@
-use crate::caster::{cast_ray, load_textures};
-use crate::framebuffer::Framebuffer;
-use crate::maze::{find_start_position, load_maze};
-use crate::player::Player;
-use crate::sfx::{play_background_music, play_sound};
-use image::{DynamicImage, GenericImageView, Rgba};
-use minifb::{Key, Scale, Window, WindowOptions};
-use nalgebra_glm::Vec2;
+use crate::bitmap::{Image, Rect};
+use crate::caster::{cast_ray, load_textures, reveal_ray};
+use crate::clock::{FixedTimestep, FrameLimiter};
+use crate::emitter::{find_emitters, Emitter};
+use crate::font::{Align, TextStyle};
+use crate::framebuffer::{BlendMode, Framebuffer};
+use crate::maze::{
+    find_start_position, floor_material, generate_maze, is_walkable, load_maze, open_direction,
+    openness, Floor, MapMode, MazeFile, CRAWL_CEILING,
+};
+use crate::minimap::{cell_color, Minimap, MinimapView};
+use crate::music::MusicPlayer;
+use crate::player::{Player, STAND_EYE_HEIGHT};
+use crate::input::{Action, InputFrame};
+use crate::layer::Layer;
+use crate::line::{Line, LineStyle};
+use crate::leaderboard::{self, Leaderboard, Run};
+use crate::replay::{Recorder, Replay};
+use crate::save::{self, SaveState};
+use crate::config::{Config, LanternConfig, WindowConfig, DEFAULT_RENDER_HEIGHT, DEFAULT_RENDER_WIDTH};
+use crate::settings::Settings;
+use crate::ttf::TrueTypeFont;
+use crate::audio_backend::{AudioLog, Reverb, SilentBackend};
+use crate::sfx::{AudioManager, Channel, SoundHandle};
+use minifb::{Key, KeyRepeat, Scale, Window, WindowOptions};
+use nalgebra_glm::{Vec2, Vec3};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 use std::time::{Duration, Instant};
 
-// Definición del enum para los estados del juego
-pub enum GameState {
-    WelcomeScreen,
-    Playing,
-    EndScreen,
-    Exiting, // Otros estados como MainMenu, GameOver, etc.
-}
-
-// Estructura principal que representa el juego
+// Estructura principal que representa el juego: ventana, mundo y audio
+// compartidos por todas las escenas
 pub struct Game {
-    pub window: Window,
-    pub state: GameState,
+    window: Option<Window>, // None al correr sin ventana (--headless)
+    pub input: InputFrame,  // Entrada del cuadro actual
+    recorder: Option<Recorder>,
+    replay: Option<Replay>,
     pub player: Player,
     pub framebuffer: Framebuffer,
-    pub maze_opt: usize,
+    pub level: Level,
     pub maze: Vec<Vec<char>>,
     pub mode: &'static str,
     pub last_frame_time: Instant,
     pub fps_counter: usize,
     pub current_fps: usize,
-    pub prev_y_pressed: bool,
-    pub m_pressed: bool,
-    pub frame_delay: Duration,
+    pub timestep: FixedTimestep,
+    pub frame_limiter: FrameLimiter,
     pub block_size: usize,
-    pub block_siz2d: usize,
-    x_offset: usize,     // Posición del sprite
-    y_offset: usize,     // Posición fija en y
-    direction: isize,
+    pub settings: Settings,
+    config_path: PathBuf, // Donde se guardan los ajustes
+    window_config: WindowConfig,
+    lantern: LanternConfig,
+    ceiling_color: u32,
+    floor_color: u32,
+    pub audio: AudioManager,
+    pub audio_log: Option<AudioLog>, // Con MAZE_AUDIO=record
+    pub music: MusicPlayer,
+    emitters: Vec<Emitter>,
+    ui_sound: Option<SoundHandle>, // Último sonido de interfaz, para no encimarlos
+    pub run: Run,                  // Tiempo y pasos de la partida en curso
+    pub maze_key: String,          // Tabla de mejores tiempos del laberinto actual
+    pub leaderboard: Leaderboard,
+    leaderboard_path: PathBuf,
+    pub last_rank: Option<usize>, // Posición de la última partida terminada
+    pub explored: Vec<Vec<bool>>, // Celdas que el jugador ya vio o pisó
+    map_mode: MapMode,
+    minimap: Minimap,
+    save_path: PathBuf,
+    pub has_save: bool,
+    pub pending_save: Option<SaveState>, // Partida que se continúa al entrar a jugar
+    notice: Option<(String, Instant)>, // Mensaje breve en pantalla y cuándo apareció
+    pub ttf: TrueTypeFont,             // Fuente para títulos sobre los menús
+    textures: HashMap<char, Image>,    // Textura de cada tipo de pared
+    map_textures: HashMap<char, Image>, // Las mismas, preparadas para el mapa 2D
+    screens: HashMap<&'static str, Image>, // Pantallas fijas ya cargadas
+    lantern_image: Image,
+    bump_flash: f32, // Segundos que le quedan al destello del choque
+    hud: Layer,      // Tiempo y FPS, arriba del cuadro
+    x_offset: f32,       // Posición del sprite
+    direction: f32,
 }
 
-impl Game {
-    pub fn new() -> Self {
-        let block_size = 30;
-        let block_siz2d = 5;
-
-        let window_width = 1080;
-        let window_height = 720;
-        let framebuffer_width = block_size * 13; //390
-        let framebuffer_height = block_size * 9; //270
-        let frame_delay = Duration::from_millis(20);
-
-        let mut window = Window::new(
-            "Maze Runner",
-            window_width,
-            window_height,
-            WindowOptions {
-                resize: false,
-                scale: Scale::FitScreen,
-                ..WindowOptions::default()
-            },
-        )
-        .unwrap();
+// De dónde sale el laberinto que se juega
+#[derive(Clone)]
+pub enum Level {
+    Builtin(usize), // Uno de LEVELS, contando desde 1
+    File(PathBuf),
+    Generated(u64), // Semilla
+}
+
+// Tamaño en celdas de los laberintos generados
+const GENERATED_SIZE: (usize, usize) = (10, 8);
+
+// Sección del manifiesto de sonidos y música de los laberintos que no son
+// de LEVELS
+const CUSTOM_SOUNDS: &str = "custom";
+const CUSTOM_MUSIC: &str = "background.mp3";
+
+const NOTICE_TIME: Duration = Duration::from_secs(2);
+
+// Lee o genera el laberinto de un nivel
+fn level_maze(level: &Level) -> std::io::Result<MazeFile> {
+    match level {
+        Level::Builtin(n) => load_maze(Path::new(LEVELS[n - 1].0)),
+        Level::File(path) => load_maze(path),
+        Level::Generated(seed) => {
+            let (width, height) = GENERATED_SIZE;
+            Ok(MazeFile {
+                cells: generate_maze(*seed, width, height),
+                map: MapMode::Explored,
+            })
+        }
+    }
+}
 
-        window.set_position(0, 0);
-        window.set_cursor_visibility(true);
+// Los generados se identifican por la semilla, los demás por el contenido
+// del archivo
+fn level_key(level: &Level, maze: &[Vec<char>]) -> String {
+    match level {
+        Level::Generated(seed) => leaderboard::seed_key(*seed),
+        _ => leaderboard::maze_key(maze),
+    }
+}
 
-        let maze = load_maze("./maze.txt");
+const HEAD_BOB_AMPLITUDE: f32 = 0.015; // Fracción de la altura de pared
+const LANTERN_IMAGE: &str = "./src/img/lantern3.png";
+const HUD_TOP: usize = 10;
+const HUD_HEIGHT: usize = HUD_TOP + 10; // Una línea de texto con su sombra
+const BUMP_FLASH_TIME: f32 = 0.15;
+const BUMP_FLASH_COLOR: u32 = 0x803020;
+
+// Archivo, sección del manifiesto de sonidos, ángulo inicial y música de
+// cada laberinto. Solo hay dos pistas largas, así que se alternan para que
+// cada cambio de nivel tenga su fundido
+const LEVELS: [(&str, &str, f32, &str); 3] = [
+    ("./maze.txt", "maze1", std::f32::consts::PI, "background.mp3"),
+    ("./maze2.txt", "maze2", std::f32::consts::PI, "papervid.mp3"),
+    ("./maze3.txt", "maze3", -std::f32::consts::PI / 2.0, "background.mp3"),
+];
+
+pub const TITLE_MUSIC: &str = "papervid.mp3";
+pub const END_MUSIC: &str = "end.mp3"; // Suena una vez, no en bucle
+const MUSIC_PLAYLIST: [&str; 2] = ["background.mp3", "papervid.mp3"];
+
+// Sonido con varias pisadas y el inicio de cada una dentro de él; se
+// alternan para variar el sonido
+struct FootstepSet {
+    sound: &'static str,
+    clips_ms: &'static [u64],
+    length_ms: u64,
+}
+
+const STONE_STEPS: FootstepSet = FootstepSet {
+    sound: "steps.mp3",
+    clips_ms: &[950, 1800, 2650, 4250],
+    length_ms: 600,
+};
+const DIRT_STEPS: FootstepSet = FootstepSet {
+    sound: "steps_dirt",
+    clips_ms: &[0, 400, 800, 1200],
+    length_ms: 350,
+};
+const WATER_STEPS: FootstepSet = FootstepSet {
+    sound: "steps_water",
+    clips_ms: &[0, 400, 800, 1200],
+    length_ms: 400,
+};
+
+// Eco en el lugar más cerrado y en el más abierto; en medio se interpola
+const REVERB_DELAY_MS: (f32, f32) = (30.0, 140.0);
+const REVERB_AMOUNT: (f32, f32) = (0.1, 0.45);
+
+// En el mapa 2D las paredes llevan su textura teñida con el color de la
+// celda; las '|' corren en vertical y su textura se gira
+fn map_textures(textures: &HashMap<char, Image>) -> HashMap<char, Image> {
+    textures
+        .iter()
+        .filter_map(|(&cell, texture)| {
+            let color = cell_color(cell)?;
+            let turns = if cell == '|' { 1 } else { 0 };
+            Some((cell, texture.rotated(turns).tinted(color)))
+        })
+        .collect()
+}
+
+// Ventana del tamaño del framebuffer multiplicado por la escala, o del
+// tamaño configurado ajustada a la pantalla
+fn create_window(
+    scale: Option<usize>,
+    fit: &WindowConfig,
+    width: usize,
+    height: usize,
+) -> Window {
+    let (window_width, window_height, scale) = match scale {
+        Some(n) => (width * n, height * n, Scale::X1),
+        None => (fit.width, fit.height, Scale::FitScreen),
+    };
+
+    let mut window = Window::new(
+        "Maze Runner",
+        window_width,
+        window_height,
+        WindowOptions {
+            resize: false,
+            scale,
+            ..WindowOptions::default()
+        },
+    )
+    .unwrap();
+
+    window.set_position(0, 0);
+    window.set_cursor_visibility(true);
+    window
+}
+
+// Cómo se crea el juego además de la configuración; viene de la línea de
+// comandos
+pub struct Launch {
+    pub headless: bool,
+    pub record: Option<Recorder>,
+    pub replay: Option<Replay>,
+}
+
+impl Game {
+    pub fn new(config: Config, config_path: PathBuf, launch: Launch) -> Self {
+        let Config {
+            window: window_config,
+            render,
+            player: player_config,
+            lantern,
+            settings,
+        } = config;
+        let block_size = render.block_size;
+        let minimap_zoom = render.minimap_block_size as f32; // Píxeles por celda al empezar
+
+        let textures = load_textures().unwrap_or_else(|e| panic!("could not load wall textures: {}", e));
+        let lantern_image = Image::load(Path::new(LANTERN_IMAGE))
+            .map(|image| image.flipped(lantern.left_hand, false))
+            .unwrap_or_else(|e| panic!("could not load lantern: {}", e));
+
+        let window = (!launch.headless)
+            .then(|| create_window(settings.window_scale, &window_config, render.width, render.height));
 
         let mut player = Player::new(
             Vec2::new(30.0, 30.0),
             Vec2::new(5.0, 5.0),
             std::f32::consts::PI / 1.0,
-            std::f32::consts::PI / 3.5,
+            settings.fov.to_radians(),
         );
-
-        if let Some((start_x, start_y)) = find_start_position(&maze, block_size) {
-            player.pos = Vec2::new(start_x as f32, start_y as f32);
-        } else {
-            panic!("No start position ('s') found in the maze!");
+        player.move_speed = player_config.move_speed;
+        player.run_multiplier = player_config.run_multiplier;
+        player.radius = player_config.radius;
+
+        let mut framebuffer = Framebuffer::new(render.width, render.height);
+        framebuffer.set_background_color(render.background_color);
+
+        // MAZE_AUDIO=silent desactiva el sonido; MAZE_AUDIO=record además
+        // anota qué sonidos se reprodujeron. Sin ventana nunca hay sonido
+        let (mut audio, audio_log) = match std::env::var("MAZE_AUDIO").as_deref() {
+            Ok("record") => {
+                let (audio, log) = AudioManager::recording();
+                (audio, Some(log))
+            }
+            Ok("silent") => (AudioManager::with_backend(Box::new(SilentBackend)), None),
+            _ if launch.headless => (AudioManager::with_backend(Box::new(SilentBackend)), None),
+            _ => (AudioManager::new(), None),
+        };
+        for error in audio.preload_level("title") {
+            eprintln!("{}", error);
         }
+        let music = MusicPlayer::new();
 
-        let mut framebuffer = Framebuffer::new(framebuffer_width, framebuffer_height);
-        framebuffer.set_background_color(0xb69f66);
+        // Sin ventana no hay a quién esperar: cada cuadro es un paso
+        let max_fps = if launch.headless { None } else { Some(render.max_fps) };
 
-        play_background_music("./src/sound/background.mp3");
-
-        Game {
+        let mut game = Game {
             window,
-            state: GameState::WelcomeScreen,
+            input: InputFrame::default(),
+            recorder: launch.record,
+            replay: launch.replay,
             player,
             framebuffer,
-            maze_opt: 1,
-            maze,
+            level: Level::Builtin(1),
+            maze: Vec::new(),
             mode: "3D",
             last_frame_time: Instant::now(),
             fps_counter: 0,
             current_fps: 0,
-            prev_y_pressed: false,
-            m_pressed: false,
-            frame_delay,
+            timestep: FixedTimestep::new(render.updates_per_second),
+            frame_limiter: FrameLimiter::new(max_fps),
             block_size,
-            block_siz2d,
-            x_offset: 260,     // Posición inicial en x
-            y_offset: 190,     // Posición fija en y
-            direction: 1,      // Dirección inicial
+            settings,
+            leaderboard: Leaderboard::load(&leaderboard::path_for(&config_path)),
+            leaderboard_path: leaderboard::path_for(&config_path),
+            save_path: save::path_for(&config_path),
+            has_save: save::path_for(&config_path).exists(),
+            config_path,
+            window_config,
+            ceiling_color: render.ceiling_color,
+            floor_color: render.floor_color,
+            audio,
+            audio_log,
+            music,
+            emitters: Vec::new(),
+            ui_sound: None,
+            run: Run::default(),
+            maze_key: String::new(),
+            last_rank: None,
+            explored: Vec::new(),
+            map_mode: MapMode::Explored,
+            minimap: Minimap::new(minimap_zoom),
+            pending_save: None,
+            notice: None,
+            ttf: TrueTypeFont::bundled(),
+            map_textures: map_textures(&textures),
+            textures,
+            screens: HashMap::new(),
+            lantern_image,
+            bump_flash: 0.0,
+            hud: Layer::new(render.width, HUD_HEIGHT),
+            x_offset: lantern.x, // Posición inicial en x
+            direction: 1.0,    // Dirección inicial
+            lantern,
+        };
+        game.apply_settings();
+        game
+    }
+
+    // Pasa los ajustes al jugador y al audio
+    pub fn apply_settings(&mut self) {
+        self.player.fov = self.settings.fov.to_radians();
+        self.player.mouse_sensitivity = self.settings.mouse_sensitivity;
+        self.audio.set_master_volume(self.settings.master_volume);
+        for (channel, volume) in self.settings.channel_volumes {
+            self.audio.set_channel_volume(channel, volume);
         }
     }
 
-    pub fn render(&mut self) {
+    // Cambiar la escala obliga a crear la ventana de nuevo
+    pub fn set_window_scale(&mut self, scale: Option<usize>) {
+        self.settings.window_scale = scale;
+        if self.window.is_some() {
+            self.window = Some(create_window(
+                scale,
+                &self.window_config,
+                self.framebuffer.width,
+                self.framebuffer.height,
+            ));
+        }
+    }
 
-        match self.state {
-            GameState::Playing => self.render_playing(),
-            GameState::WelcomeScreen => self.render_tittle_screen(),
-            GameState::EndScreen => self.render_end_screen(),
-            GameState::Exiting => self.render_exit(),
+    pub fn is_open(&self) -> bool {
+        self.window.as_ref().is_none_or(|window| window.is_open())
+    }
+
+    pub fn set_cursor_visible(&mut self, visible: bool) {
+        if let Some(window) = &mut self.window {
+            window.set_cursor_visibility(visible);
         }
     }
-    fn render_exit(&mut self){
-        self.framebuffer.clear();
-        self.framebuffer.set_background_color(0xffffff);
+
+    // Si la partida la juega alguien en la ventana; las corridas sin ventana
+    // o reproducidas no guardan nada en disco
+    pub fn is_live(&self) -> bool {
+        self.window.is_some() && self.replay.is_none()
     }
 
-    fn render_tittle_screen(&mut self) {
-        self.framebuffer.clear();
-        self.framebuffer
-            .draw_image("./src/img/tittleScreen.png", 0, 0);
+    // Lee la entrada de este cuadro: de la grabación si se está
+    // reproduciendo una, de la ventana, o ninguna tecla si no hay ventana.
+    // Devuelve false cuando la grabación se terminó
+    pub fn begin_frame(&mut self) -> bool {
+        self.input = match &mut self.replay {
+            Some(replay) => match replay.next_frame() {
+                Some(Ok(frame)) => frame,
+                Some(Err(e)) => {
+                    eprintln!("replay: {}", e);
+                    return false;
+                }
+                None => return false,
+            },
+            None => match &self.window {
+                Some(window) => InputFrame::from_window(window, self.timestep.advance()),
+                None => InputFrame::idle(1),
+            },
+        };
 
-        self.window
-            .update_with_buffer(
-                &self.framebuffer.buffer,
-                self.framebuffer.width,
-                self.framebuffer.height,
-            )
-            .unwrap();
+        if let Some(recorder) = &mut self.recorder {
+            if let Err(e) = recorder.write(&self.input) {
+                eprintln!("could not record input: {}", e);
+                self.recorder = None;
+            }
+        }
+        true
+    }
 
-        if self.window.is_key_down(Key::Key1) {
-            self.maze = load_maze("./maze.txt");
+    pub fn save_settings(&self) {
+        if let Err(e) = Config::save_settings(&self.config_path, &self.settings) {
+            eprintln!("could not save {}: {}", self.config_path.display(), e);
+        }
+    }
 
-            if let Some((start_x, start_y)) = find_start_position(&self.maze, self.block_size) {
-                self.player.a = std::f32::consts::PI / 1.0;
-                self.player.pos = Vec2::new(start_x as f32, start_y as f32);
-            } else {
-                panic!("No start position ('s') found in the maze!");
+    // Dibuja una pantalla fija (título, final) estirada a todo el cuadro;
+    // la imagen se carga la primera vez que se usa
+    pub fn draw_screen(&mut self, path: &'static str) {
+        let image = self
+            .screens
+            .entry(path)
+            .or_insert_with(|| Image::load(Path::new(path)).unwrap_or_else(|e| panic!("could not load screen: {}", e)));
+        let (width, height) = (self.framebuffer.width, self.framebuffer.height);
+        self.framebuffer.clear();
+        self.framebuffer.blit_scaled(image, 0, 0, width, height);
+    }
+
+    // Muestra en la ventana lo que se dibujó en el framebuffer
+    pub fn present(&mut self) {
+        if let Some(window) = &mut self.window {
+            window
+                .update_with_buffer(
+                    &self.framebuffer.buffer,
+                    self.framebuffer.width,
+                    self.framebuffer.height,
+                )
+                .unwrap();
+        }
+    }
+
+    // Carga el laberinto elegido, coloca al jugador y arranca sus sonidos
+    pub fn start_level(&mut self, level: Level) {
+        let (sound_section, angle, music) = match &level {
+            Level::Builtin(n) => {
+                let (_, sound_section, angle, music) = LEVELS[n - 1];
+                (sound_section, Some(angle), music)
             }
+            Level::File(_) | Level::Generated(_) => (CUSTOM_SOUNDS, None, CUSTOM_MUSIC),
+        };
+        let maze = level_maze(&level).unwrap_or_else(|e| panic!("could not load the maze: {}", e));
+        self.maze = maze.cells;
+        self.map_mode = maze.map;
+        self.minimap.reset(&self.maze);
+        self.explored = self.maze.iter().map(|row| vec![false; row.len()]).collect();
+
+        if let Some((start_x, start_y)) = find_start_position(&self.maze, self.block_size) {
+            self.player.pos = Vec2::new(start_x as f32, start_y as f32);
+            // Los laberintos sin ángulo propio miran hacia el pasillo libre
+            self.player.a = angle.unwrap_or_else(|| {
+                let col = (self.player.pos.x / self.block_size as f32) as usize;
+                let row = (self.player.pos.y / self.block_size as f32) as usize;
+                open_direction(&self.maze, col, row)
+            });
+        } else {
+            panic!("No start position ('s') found in the maze!");
+        }
+
+        self.preload_sounds(sound_section);
+        if self.settings.shuffle_music {
+            self.music.play_shuffled(&mut self.audio, &MUSIC_PLAYLIST);
+        } else {
+            self.music.play(&mut self.audio, music, true);
+        }
+
+        self.stop_emitters();
+        self.emitters = find_emitters(&self.maze, self.block_size);
+        for emitter in &mut self.emitters {
+            emitter.start(&mut self.audio);
+        }
 
-            play_sound("./src/sound/mapSelect.mp3");
-            self.maze_opt = 1;
-            self.state = GameState::Playing;
+        self.maze_key = level_key(&level, &self.maze);
+        self.run = Run::default();
+        self.level = level;
+        self.timestep.reset();
+    }
+
+    pub fn stop_emitters(&mut self) {
+        for emitter in &mut self.emitters {
+            emitter.stop(&mut self.audio);
         }
+    }
 
-        if self.window.is_key_down(Key::Key2) {
-            self.maze = load_maze("./maze2.txt");
+    // Atajos de audio disponibles en cualquier escena
+    pub fn handle_audio_shortcuts(&mut self) {
+        if self.input.is_pressed(Key::N, KeyRepeat::No) {
+            self.audio.toggle_mute();
+        }
 
-            if let Some((start_x, start_y)) = find_start_position(&self.maze, self.block_size) {
-                self.player.a = std::f32::consts::PI / 1.0;
-                self.player.pos = Vec2::new(start_x as f32, start_y as f32);
+        // Con Shift se cambia solo la música
+        let step = if self.input.is_pressed(Key::Minus, KeyRepeat::Yes) {
+            -0.1
+        } else if self.input.is_pressed(Key::Equal, KeyRepeat::Yes) {
+            0.1
+        } else {
+            return;
+        };
+        if self.input.is_down(Key::LeftShift) || self.input.is_down(Key::RightShift) {
+            let volume = self.audio.channel_volume(Channel::Music);
+            self.audio.set_channel_volume(Channel::Music, volume + step);
+            self.settings
+                .set_channel_volume(Channel::Music, self.audio.channel_volume(Channel::Music));
+        } else {
+            self.audio.set_master_volume(self.audio.master_volume() + step);
+            self.settings.master_volume = self.audio.master_volume();
+        }
+    }
+
+    // Sonidos de interfaz: si el anterior sigue sonando se corta
+    pub fn play_ui_sound(&mut self, name: &str) {
+        if let Some(handle) = self.ui_sound.take() {
+            self.audio.stop(handle);
+        }
+        self.ui_sound = self.audio.play(Channel::Ui, name).map_err(|e| eprintln!("{}", e)).ok();
+    }
+
+    pub fn play_sfx(&mut self, name: &str) {
+        if let Err(e) = self.audio.play(Channel::Sfx, name) {
+            eprintln!("{}", e);
+        }
+    }
+
+    // Eco según qué tan abierto está el lugar del jugador: corto y suave en
+    // pasillos estrechos, más largo y fuerte en espacios amplios
+    fn room_reverb(&self) -> Reverb {
+        let col = (self.player.pos.x / self.block_size as f32) as usize;
+        let row = (self.player.pos.y / self.block_size as f32) as usize;
+        let open = openness(&self.maze, col, row);
+
+        let lerp = |(closed, wide): (f32, f32)| closed + (wide - closed) * open;
+        Reverb {
+            delay: Duration::from_millis(lerp(REVERB_DELAY_MS) as u64),
+            amount: lerp(REVERB_AMOUNT),
+        }
+    }
+
+    fn preload_sounds(&mut self, level: &str) {
+        for error in self.audio.preload_level(level) {
+            eprintln!("{}", error);
+        }
+    }
+
+    // Teclas que se leen una vez por cuadro mientras se juega
+    pub fn handle_playing_input(&mut self) {
+        if self.settings.bindings.is_pressed(&self.input, Action::Map) {
+            if self.map_mode == MapMode::Hidden {
+                self.show_notice("NO MAP IN THIS MAZE");
             } else {
-                panic!("No start position ('s') found in the maze!");
+                self.mode = if self.mode == "2D" { "3D" } else { "2D" };
+                self.play_ui_sound("digimap.mp3");
             }
+        }
 
-            play_sound("./src/sound/mapSelect.mp3");
-            self.maze_opt = 2;
-            self.state = GameState::Playing;
+        if self.settings.bindings.is_pressed(&self.input, Action::MapZoomIn) {
+            self.minimap.adjust_zoom(1.0);
+        }
+        if self.settings.bindings.is_pressed(&self.input, Action::MapZoomOut) {
+            self.minimap.adjust_zoom(-1.0);
         }
 
-        if self.window.is_key_down(Key::Key3) {
-            self.maze = load_maze("./maze3.txt");
+        if self.settings.bindings.is_pressed(&self.input, Action::MouseLook) {
+            self.play_ui_sound("digicam.mp3");
+            self.player.mouse_control = !self.player.mouse_control;
+            // Ocultamos el cursor mientras se controla la cámara con el mouse
+            self.set_cursor_visible(!self.player.mouse_control);
+        }
 
-            if let Some((start_x, start_y)) = find_start_position(&self.maze, self.block_size) {
-                self.player.a = -std::f32::consts::PI / 2.0;
-                self.player.pos = Vec2::new(start_x as f32, start_y as f32);
-            } else {
-                panic!("No start position ('s') found in the maze!");
+        self.handle_mouse_settings();
+        self.handle_effect_settings();
+        self.player.process_mouse(&self.input);
+    }
+
+    // Ajustes del mouse que se leen una vez por cuadro
+    fn handle_mouse_settings(&mut self) {
+        if self.input.is_pressed(Key::LeftBracket, KeyRepeat::Yes) {
+            self.player.adjust_mouse_sensitivity(0.8);
+            self.settings.mouse_sensitivity = self.player.mouse_sensitivity;
+        }
+
+        if self.input.is_pressed(Key::RightBracket, KeyRepeat::Yes) {
+            self.player.adjust_mouse_sensitivity(1.25);
+            self.settings.mouse_sensitivity = self.player.mouse_sensitivity;
+        }
+
+        if self.input.is_pressed(Key::V, KeyRepeat::No) {
+            self.player.toggle_vertical_look();
+        }
+    }
+
+    // F1, F2 y F3 activan o desactivan cada efecto de la caminata
+    fn handle_effect_settings(&mut self) {
+        if self.input.is_pressed(Key::F1, KeyRepeat::No) {
+            self.settings.head_bob = !self.settings.head_bob;
+        }
+
+        if self.input.is_pressed(Key::F2, KeyRepeat::No) {
+            self.settings.lantern_sway = !self.settings.lantern_sway;
+        }
+
+        if self.input.is_pressed(Key::F3, KeyRepeat::No) {
+            self.settings.footstep_sounds = !self.settings.footstep_sounds;
+        }
+    }
+
+    // Guarda la partida en curso para continuarla después
+    pub fn save_game(&mut self) {
+        if !self.is_live() {
+            return;
+        }
+        let explored = self
+            .explored
+            .iter()
+            .enumerate()
+            .flat_map(|(row, cells)| {
+                cells
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, &seen)| seen)
+                    .map(move |(col, _)| (col, row))
+            })
+            .collect();
+        let state = SaveState {
+            level: self.level.clone(),
+            maze_key: self.maze_key.clone(),
+            position: (self.player.pos.x, self.player.pos.y),
+            angle: self.player.a,
+            pitch: self.player.pitch,
+            run: self.run,
+            lantern: (self.x_offset, self.direction),
+            explored,
+        };
+
+        match state.save(&self.save_path) {
+            Ok(()) => {
+                self.has_save = true;
+                self.show_notice("GAME SAVED");
+            }
+            Err(e) => {
+                eprintln!("could not save {}: {}", self.save_path.display(), e);
+                self.show_notice("COULD NOT SAVE");
             }
+        }
+    }
 
-            play_sound("./src/sound/mapSelect.mp3");
-            self.maze_opt = 3;
-            self.state = GameState::Playing;
+    // Lee la partida guardada y revisa que siga valiendo para su laberinto
+    pub fn read_save(&self) -> Result<SaveState, String> {
+        let state = SaveState::load(&self.save_path)?;
+        let maze = level_maze(&state.level).map_err(|e| e.to_string())?.cells;
+        if level_key(&state.level, &maze) != state.maze_key {
+            return Err("the maze changed since the game was saved".into());
         }
 
-        if self.window.is_key_down(Key::Escape) {
-            self.state = GameState::Exiting; // Cambia al estado de salir
+        let (x, y) = state.position;
+        let cell = (x >= 0.0 && y >= 0.0)
+            .then(|| {
+                let col = (x / self.block_size as f32) as usize;
+                let row = (y / self.block_size as f32) as usize;
+                maze.get(row).and_then(|cells| cells.get(col)).copied()
+            })
+            .flatten();
+        if !cell.is_some_and(is_walkable) {
+            return Err("the saved position is inside a wall".into());
         }
+        Ok(state)
     }
 
-    fn render_end_screen(&mut self) {
-        self.framebuffer.clear();
-        self.framebuffer.draw_image("./src/img/endScreen.png", 0, 0);
+    // Empieza el laberinto guardado y deja todo como estaba
+    pub fn restore(&mut self, state: SaveState) {
+        self.start_level(state.level);
+        self.player.pos = Vec2::new(state.position.0, state.position.1);
+        self.player.a = state.angle;
+        self.player.pitch = state.pitch;
+        self.run = state.run;
+        self.x_offset = state.lantern.0.clamp(self.lantern.min_x, self.lantern.max_x);
+        self.direction = if state.lantern.1 < 0.0 { -1.0 } else { 1.0 };
+        for (col, row) in state.explored {
+            if let Some(seen) = self.explored.get_mut(row).and_then(|cells| cells.get_mut(col)) {
+                *seen = true;
+            }
+        }
+    }
 
-        self.window
-            .update_with_buffer(
-                &self.framebuffer.buffer,
-                self.framebuffer.width,
-                self.framebuffer.height,
-            )
-            .unwrap();
+    pub fn show_notice(&mut self, text: &str) {
+        self.notice = Some((text.to_string(), Instant::now()));
+    }
+
+    // El mensaje de show_notice mientras no haya expirado
+    pub fn notice(&self) -> Option<&str> {
+        self.notice
+            .as_ref()
+            .filter(|(_, shown)| shown.elapsed() < NOTICE_TIME)
+            .map(|(text, _)| text.as_str())
+    }
+
+    // Si el jugador está parado en la salida
+    pub fn reached_goal(&self) -> bool {
+        self.player.get_current_tile(&self.maze, self.block_size) == Some('g')
+    }
+
+    // Anota la partida en la tabla del laberinto y la guarda
+    pub fn finish_run(&mut self) {
+        self.last_rank = self.leaderboard.insert(&self.maze_key, self.run);
+        if self.last_rank.is_some() && self.is_live() {
+            if let Err(e) = self.leaderboard.save(&self.leaderboard_path) {
+                eprintln!("could not save {}: {}", self.leaderboard_path.display(), e);
+            }
+        }
+    }
+
+    // Un paso fijo de la simulación del laberinto
+    pub fn update_playing(&mut self, dt: f32) {
+        self.player
+            .process_events(&self.input, &self.settings.bindings, &self.maze, self.block_size, dt);
+
+        self.run.time += dt;
+        let col = (self.player.pos.x / self.block_size as f32) as usize;
+        let row = (self.player.pos.y / self.block_size as f32) as usize;
+        if let Some(seen) = self.explored.get_mut(row).and_then(|cells| cells.get_mut(col)) {
+            *seen = true;
+        }
+        self.minimap.update(self.player.pos, self.block_size);
+
+        let stepped = self.player.take_footstep();
+        if stepped {
+            self.run.steps += 1;
+        }
+
+        let footstep = stepped && self.settings.footstep_sounds;
+        let bump = self.player.take_bump();
+        if footstep || bump {
+            let reverb = self.room_reverb();
+            self.audio.set_reverb(Some(reverb));
+        }
+
+        if footstep {
+            let current_tile = self.player.get_current_tile(&self.maze, self.block_size);
+            let set = match current_tile.map(floor_material) {
+                Some(Floor::Dirt) => &DIRT_STEPS,
+                Some(Floor::Water) => &WATER_STEPS,
+                _ => &STONE_STEPS,
+            };
+            let clip = set.clips_ms[self.player.walk_phase as usize % set.clips_ms.len()];
+            let result = self.audio.play_clip(
+                Channel::Sfx,
+                set.sound,
+                Duration::from_millis(clip),
+                Duration::from_millis(set.length_ms),
+            );
+            if let Err(e) = result {
+                eprintln!("{}", e);
+            }
+        }
 
-        if self.window.is_key_down(Key::Enter) {
-            play_sound("./src/sound/win4.mp3");
-            self.state = GameState::WelcomeScreen;
+        if bump {
+            self.play_sfx("bump");
+            self.bump_flash = BUMP_FLASH_TIME;
         }
+        self.bump_flash = (self.bump_flash - dt).max(0.0);
 
-        if self.window.is_key_down(Key::Escape) {
-            self.state = GameState::Exiting; // Cambia al estado de salir
+        for emitter in &mut self.emitters {
+            emitter.update(&mut self.audio, &self.player, &self.maze, self.block_size);
         }
 
-        // Detener el sonido de caminar
-        self.player.stop_walking_sound();
+        // Actualiza la posición en x del farol
+        self.x_offset += self.direction * self.lantern.sway_speed * dt;
+
+        // Cambia la dirección cuando alcanza un límite
+        if self.x_offset > self.lantern.max_x || self.x_offset < self.lantern.min_x {
+            self.x_offset = self.x_offset.clamp(self.lantern.min_x, self.lantern.max_x);
+            self.direction *= -1.0;
+        }
     }
 
-    fn render_playing(&mut self) {
+    // Dibuja el laberinto, el farol y los FPS en el framebuffer
+    pub fn draw_playing(&mut self) {
+        // Oscurece hacia los bordes, como si el farol solo alumbrara el
+        // centro; `strength` es cuánto se oscurecen las esquinas
+        fn draw_vignette(framebuffer: &mut Framebuffer, strength: f32) {
+            if strength <= 0.0 {
+                return;
+            }
+            let (cx, cy) = (framebuffer.width as f32 / 2.0, framebuffer.height as f32 / 2.0);
+            let corner = cx * cx + cy * cy;
+            framebuffer.set_blend_mode(BlendMode::Multiply);
+            for y in 0..framebuffer.height {
+                for x in 0..framebuffer.width {
+                    let (dx, dy) = (x as f32 - cx, y as f32 - cy);
+                    let light = 1.0 - strength * (dx * dx + dy * dy) / corner;
+                    let level = (light.clamp(0.0, 1.0) * 255.0) as u32;
+                    framebuffer.blend_point(x, y, level << 16 | level << 8 | level, 1.0);
+                }
+            }
+            framebuffer.set_blend_mode(BlendMode::Normal);
+        }
+
         fn draw_cell(
             framebuffer: &mut Framebuffer,
             xo: usize,
             yo: usize,
             block_size: usize,
             cell: char,
+            textures: &HashMap<char, Image>,
         ) {
-            if cell == ' ' || cell == 's' {
+            let Some(color) = cell_color(cell) else {
+                return;
+            };
+
+            // Cada celda muestra un cuarto de la textura para que el dibujo
+            // se distinga a este tamaño
+            if let Some(texture) = textures.get(&cell) {
+                let (width, height) = (texture.width / 2, texture.height / 2);
+                let source = Rect {
+                    x: xo / block_size % 2 * width,
+                    y: yo / block_size % 2 * height,
+                    width,
+                    height,
+                };
+                framebuffer.blit_region(texture, source, xo as isize, yo as isize, block_size, block_size);
                 return;
             }
-
-            if cell == '+' {
-                framebuffer.set_current_color(0x011f4b);
-            }
-
-            if cell == '-' {
-                framebuffer.set_current_color(0x005b96);
-            }
-
-            if cell == '|' {
-                framebuffer.set_current_color(0xb3cde0);
-            }
-
-            if cell == 'g' {
-                framebuffer.set_current_color(0xffbf00);
-            }
+            framebuffer.set_current_color(color);
 
             for x in xo..xo + block_size {
                 for y in yo..yo + block_size {
@@ -262,205 +841,267 @@ impl Game {
             ((r as u32) << 16) | ((g as u32) << 8) | (b as u32)
         }
 
+        #[allow(clippy::too_many_arguments)]
         fn render3d(
             framebuffer: &mut Framebuffer,
+            textures: &HashMap<char, Image>,
             player: &Player,
-            maze: &Vec<Vec<char>>,
+            maze: &[Vec<char>],
             block_size: usize,
+            eye_offset: f32,
+            (ceiling_color, floor_color): (u32, u32),
+            explored: &mut [Vec<bool>],
         ) {
             let num_rays = framebuffer.width;
-            let (texture_plus, texture_minus, texture_pipe, texture_g) = load_textures();
 
-            let hw = framebuffer.width as f32 / 2.0;
             let hh = framebuffer.height as f32 / 2.0;
             let max_distance = 120.0; // Ajusta esto según tu necesidad
 
+            // El horizonte se desplaza con la inclinación vertical de la cámara
+            let horizon = hh + player.pitch;
+            let eye = player.eye_height + eye_offset;
+
+            // Escala del degradado según la altura de los ojos: agachado el
+            // piso se ve más cerca y el techo más lejos
+            let floor_scale = STAND_EYE_HEIGHT / eye;
+            let ceiling_scale = (1.0 - STAND_EYE_HEIGHT) / (1.0 - eye);
+
             // Dibujar el degradado en el techo y el piso
-            for y in 0..hh as usize {
-                let distance_ratio = y as f32 / hh;
-                let ceiling_color = interpolate_color(0x252423, 0x000000, distance_ratio);
-                framebuffer.set_current_color(ceiling_color);
+            for y in 0..framebuffer.height {
+                let (start, distance_ratio) = if (y as f32) < horizon {
+                    (ceiling_color, 1.0 - (horizon - y as f32) * ceiling_scale / hh)
+                } else {
+                    (floor_color, 1.0 - (y as f32 - horizon) * floor_scale / hh)
+                };
+                let color = interpolate_color(start, 0x000000, distance_ratio.clamp(0.0, 1.0));
+                framebuffer.set_current_color(color);
                 for i in 0..framebuffer.width {
                     framebuffer.point(i, y);
                 }
-
-                let floor_color = interpolate_color(0x5b6567, 0x000000, distance_ratio);
-                framebuffer.set_current_color(floor_color);
-                for i in 0..framebuffer.width {
-                    framebuffer.point(i, framebuffer.height - y - 1); // Asegúrate de no exceder los límites
-                }
             }
 
+            let distance_to_projection_plane = 90.0;
+
             for i in 0..num_rays {
                 let current_ray = i as f32 / num_rays as f32;
                 let a = player.a - (player.fov / 2.0) + (player.fov * current_ray);
-                let intersect = cast_ray(framebuffer, &maze, &player, a, block_size, false, false);
+                let intersect = cast_ray(maze, player, a, block_size, false);
+                reveal_ray(explored, player.pos, a, intersect.distance, block_size);
 
                 let distance_to_wall = intersect.distance;
-                let distance_to_projection_plane = 90.0;
+                // Altura en pantalla de una pared completa a esta distancia
                 let stake_height = (hh / distance_to_wall) * distance_to_projection_plane;
 
-                let stake_top = (hh - (stake_height / 2.0)) as usize;
-                let stake_bottom = (hh + (stake_height / 2.0)) as usize;
+                let stake_top = horizon - (1.0 - eye) * stake_height;
+                let stake_bottom = horizon + eye * stake_height;
 
-                let texture = match intersect.impact {
-                    '+' => &texture_plus,
-                    '-' => &texture_minus,
-                    '|' => &texture_pipe,
-                    'g' => &texture_g,
-                    _ => continue,
-                };
-
-                for y in stake_top..stake_bottom {
-                    let tex_y =
-                        ((y as f32 - stake_top as f32) / stake_height) * texture.height() as f32;
-                    let color = texture.get_pixel(
-                        (intersect.tex_coord * texture.width() as f32) as u32,
-                        tex_y as u32,
+                if let Some(texture) = textures.get(&intersect.impact) {
+                    draw_stake(
+                        framebuffer,
+                        texture,
+                        i,
+                        stake_top,
+                        (stake_top, stake_bottom),
+                        stake_height,
+                        intersect.tex_coord,
+                        (1.0 - (distance_to_wall / max_distance)).clamp(0.0, 1.0),
                     );
+                }
 
-                    // Calcular la opacidad en función de la distancia
-                    let opacity = (1.0 - (distance_to_wall / max_distance)).clamp(0.0, 1.0);
-                    let blended_color = blend_color_with_opacity(color, opacity);
-
-                    framebuffer.set_current_color(
-                        (blended_color[0] as u32) << 16
-                            | (blended_color[1] as u32) << 8
-                            | blended_color[2] as u32,
-                    );
-                    framebuffer.point(i, y);
+                // Techo bajo: el dintel frontal y la parte inferior del pasadizo
+                if let Some(span) = &intersect.crawl {
+                    let near = span.start.max(1.0);
+                    let far = span.end.max(near);
+                    let near_height = (hh / near) * distance_to_projection_plane;
+                    let far_height = (hh / far) * distance_to_projection_plane;
+
+                    let lintel_top = horizon - (1.0 - eye) * near_height;
+                    let lintel_bottom = horizon - (CRAWL_CEILING - eye) * near_height;
+                    let opacity = (1.0 - (near / max_distance)).clamp(0.0, 1.0);
+
+                    // Dentro del pasadizo no se ve la cara frontal
+                    let underside_start = if span.start > 0.0 {
+                        draw_stake(
+                            framebuffer,
+                            &textures[&'-'],
+                            i,
+                            lintel_top,
+                            (lintel_top, lintel_bottom),
+                            near_height,
+                            span.tex_coord,
+                            opacity,
+                        );
+                        lintel_bottom
+                    } else {
+                        0.0
+                    };
+
+                    let underside_end = horizon - (CRAWL_CEILING - eye) * far_height;
+                    let shade = interpolate_color(0x3a3633, 0x000000, 1.0 - opacity);
+                    framebuffer.set_current_color(shade);
+                    let y_start = underside_start.max(0.0) as usize;
+                    let y_end = underside_end.min(framebuffer.height as f32).max(0.0) as usize;
+                    for y in y_start..y_end {
+                        framebuffer.point(i, y);
+                    }
                 }
             }
         }
 
-        fn blend_color_with_opacity(color: Rgba<u8>, opacity: f32) -> Rgba<u8> {
-            // Color negro
-            let black = Rgba([0, 0, 0, 0]);
+        // Dibuja una columna texturizada entre `span.0` y `span.1`; `top` y
+        // `full_height` corresponden a la pared completa para mapear la textura
+        #[allow(clippy::too_many_arguments)]
+        fn draw_stake(
+            framebuffer: &mut Framebuffer,
+            texture: &Image,
+            x: usize,
+            top: f32,
+            span: (f32, f32),
+            full_height: f32,
+            tex_coord: f32,
+            opacity: f32,
+        ) {
+            let y_start = span.0.max(0.0) as usize;
+            let y_end = span.1.min(framebuffer.height as f32).max(0.0) as usize;
+            let tex_x = ((tex_coord * texture.width as f32) as usize).min(texture.width - 1);
+
+            for y in y_start..y_end {
+                let tex_y = ((y as f32 - top) / full_height) * texture.height as f32;
+                let color = texture.pixel(tex_x, (tex_y as usize).min(texture.height - 1));
 
+                // Calcular la opacidad en función de la distancia
+                framebuffer.set_current_color(blend_color_with_opacity(color, opacity));
+                framebuffer.point(x, y);
+            }
+        }
+
+        fn blend_color_with_opacity(color: u32, opacity: f32) -> u32 {
             // Mezclar el color con el negro basado en la opacidad
-            Rgba([
-                ((color[0] as f32 * opacity + black[0] as f32 * (1.0 - opacity)) as u8),
-                ((color[1] as f32 * opacity + black[1] as f32 * (1.0 - opacity)) as u8),
-                ((color[2] as f32 * opacity + black[2] as f32 * (1.0 - opacity)) as u8),
-                color[3], // Mantener el canal alfa del color original
-            ])
+            let channel = |shift: u32| ((((color >> shift) & 0xFF) as f32 * opacity) as u32) << shift;
+            channel(16) | channel(8) | channel(0)
         }
 
+        #[allow(clippy::too_many_arguments)]
         fn render2d(
             framebuffer: &mut Framebuffer,
+            textures: &HashMap<char, Image>,
             player: &mut Player,
-            maze: &Vec<Vec<char>>,
+            maze: &[Vec<char>],
             block_size: usize,
             block_siz2d: usize, // Cambié el nombre del parámetro para reflejar el tamaño del bloque en 2D
             view: bool,
+            explored: Option<&[Vec<bool>]>, // None = se ve todo el laberinto
         ) {
             player.update2d_position(block_size, block_siz2d);
 
-            // Dibujar el laberinto
-            for row in 0..maze.len() {
-                for col in 0..maze[row].len() {
+            // Dibujar el laberinto; sin explorar solo se ve lo ya visto
+            for (row, row_data) in maze.iter().enumerate() {
+                for (col, &cell) in row_data.iter().enumerate() {
+                    if explored.is_some_and(|seen| !seen[row][col]) {
+                        continue;
+                    }
                     draw_cell(
                         framebuffer,
                         col * block_siz2d,
                         row * block_siz2d,
                         block_siz2d,
-                        maze[row][col],
+                        cell,
+                        textures,
                     );
                 }
             }
 
-            // Lanzar rayos
+            // Lanzar rayos; se dibujan suavizados hasta donde chocan
             let num_rays = 5;
+            let origin = Vec3::new(player.pos2d.x, player.pos2d.y, 0.0);
+            framebuffer.set_current_color(0xFFFFFF);
             for i in 0..num_rays {
                 let current_ray = i as f32 / num_rays as f32;
                 let a = player.a - (player.fov / 2.0) + (player.fov * current_ray);
 
-                cast_ray(framebuffer, &maze, &player, a, block_siz2d, view, true);
+                let intersect = cast_ray(maze, player, a, block_siz2d, true);
+                if view {
+                    let hit = origin + Vec3::new(a.cos(), a.sin(), 0.0) * intersect.distance;
+                    framebuffer.line_aa(origin, hit);
+                }
             }
 
-            // Dibujar al jugador
+            // Dibujar al jugador, con una marca hacia donde mira
             framebuffer.set_current_color(0xFFDDD);
-            framebuffer.point(player.pos2d.x as usize, player.pos2d.y as usize);
-        }
-
-        if self.window.is_key_down(Key::Escape) {
-            self.state = GameState::Exiting; // Cambia al estado de salir
-        }
-
-        let current_tile = self.player.get_current_tile(&self.maze, self.block_size);
-
-        if let Some('g') = current_tile {
-            play_sound("./src/sound/win2.mp3");
-            self.state = GameState::EndScreen;
-        }
-
-        if self.window.is_key_down(Key::M) {
-            if !self.m_pressed {
-                self.mode = if self.mode == "2D" { "3D" } else { "2D" };
-                self.m_pressed = true;
-                play_sound("./src/sound/digimap.mp3");
-            }
-        } else {
-            self.m_pressed = false;
-        }
-
-        let is_y_pressed = self.window.is_key_down(Key::Y);
-
-        if is_y_pressed && !self.prev_y_pressed {
-            play_sound("./src/sound/digicam.mp3");
-            self.player.mouse_control = !self.player.mouse_control;
+            let heading = origin + Vec3::new(player.a.cos(), player.a.sin(), 0.0) * (block_siz2d as f32 / 2.0);
+            framebuffer.line_styled(origin, heading, &LineStyle { thickness: 2, ..LineStyle::default() });
         }
 
-        self.prev_y_pressed = is_y_pressed;
-
-        self.player
-            .process_events(&self.window, &self.maze, self.block_size);
-
         self.framebuffer.clear();
 
         if self.mode == "3D" {
+            let eye_offset = if self.settings.head_bob {
+                self.player.walk_bob() * HEAD_BOB_AMPLITUDE
+            } else {
+                0.0
+            };
+
             render3d(
                 &mut self.framebuffer,
+                &self.textures,
                 &self.player,
                 &self.maze,
                 self.block_size,
+                eye_offset,
+                (self.ceiling_color, self.floor_color),
+                &mut self.explored,
             );
-            render2d(
-                &mut self.framebuffer,
-                &mut self.player,
-                &self.maze,
-                self.block_size,
-                self.block_siz2d,
-                true,
-            );
+            draw_vignette(&mut self.framebuffer, self.lantern.vignette);
+            if self.map_mode != MapMode::Hidden {
+                let view = MinimapView {
+                    shape: self.settings.minimap_shape,
+                    rotate: self.settings.minimap_rotate,
+                    maze: &self.maze,
+                    explored: (self.map_mode == MapMode::Explored).then_some(self.explored.as_slice()),
+                    block_size: self.block_size,
+                };
+                self.minimap.draw(&mut self.framebuffer, &self.player, &view);
+            }
 
+            // El farol se balancea solo y además sigue el ciclo de caminata
+            let (lantern_x, lantern_y) = if self.settings.lantern_sway {
+                (
+                    self.x_offset + self.player.walk_sway() * self.lantern.walk_sway_x,
+                    self.lantern.y - self.player.walk_bob() * self.lantern.walk_bob_y,
+                )
+            } else {
+                (self.lantern.x, self.lantern.y)
+            };
+            let lantern_x = lantern_x + self.framebuffer.width as f32 - DEFAULT_RENDER_WIDTH as f32;
+            let lantern_y = lantern_y + self.framebuffer.height as f32 - DEFAULT_RENDER_HEIGHT as f32;
+
+            // Con el farol en la mano izquierda se refleja su posición
+            let lantern_x = if self.lantern.left_hand {
+                self.framebuffer.width as f32 - lantern_x - self.lantern_image.width as f32
+            } else {
+                lantern_x
+            };
             self.framebuffer
-                .draw_image("./src/img/lantern3.png", self.x_offset, self.y_offset);
-            // Actualiza la posición en x
-            self.x_offset = (self.x_offset as isize + self.direction) as usize;
-
-            // Cambia la dirección cuando alcanza un límite
-            if self.x_offset > 270 || self.x_offset < 255 {
-                self.direction *= -1;
-            }
+                .blit(&self.lantern_image, lantern_x as isize, lantern_y as isize);
         } else {
             render2d(
                 &mut self.framebuffer,
+                &self.map_textures,
                 &mut self.player,
                 &self.maze,
                 self.block_size,
                 self.block_size,
                 true,
+                (self.map_mode == MapMode::Explored).then_some(self.explored.as_slice()),
             );
         }
 
-        self.window
-            .update_with_buffer(
-                &self.framebuffer.buffer,
-                self.framebuffer.width,
-                self.framebuffer.height,
-            )
-            .unwrap();
+        // Destello al chocar con una pared; Screen aclara sin tapar la imagen
+        if self.bump_flash > 0.0 {
+            self.framebuffer.set_blend_mode(BlendMode::Screen);
+            self.framebuffer.overlay(BUMP_FLASH_COLOR, self.bump_flash / BUMP_FLASH_TIME);
+            self.framebuffer.set_blend_mode(BlendMode::Normal);
+        }
 
         self.fps_counter += 1;
         if self.last_frame_time.elapsed() >= Duration::from_secs(1) {
@@ -469,19 +1110,21 @@ impl Game {
             self.last_frame_time = Instant::now();
         }
 
-        self.framebuffer.set_current_color(0xFFFFFF); // Establece el color blanco para el texto
-        self.framebuffer
-            .draw_text(220, 10, &format!("FPS: {}", self.current_fps)); // Dibuja los FPS
-
-        self.window
-            .update_with_buffer(
-                &self.framebuffer.buffer,
-                self.framebuffer.width,
-                self.framebuffer.height,
-            )
-            .unwrap();
-
-        std::thread::sleep(self.frame_delay);
+        // El texto solo se vuelve a dibujar en su capa cuando cambia, así que
+        // en el HUD el tiempo va en segundos enteros y no en centésimas
+        let seconds = self.run.time as u64;
+        let time = format!("TIME: {}:{:02}", seconds / 60, seconds % 60);
+        let fps = format!("FPS: {}", self.current_fps);
+        if self.hud.needs_redraw((&time, &fps)) {
+            // Texto blanco con sombra para que se lea sobre paredes claras
+            let style = TextStyle { shadow: Some(0x000000), ..TextStyle::default() };
+            let right = TextStyle { align: Align::Right, ..style };
+            let layer = &mut self.hud.framebuffer;
+            let width = layer.width;
+            layer.draw_text_styled(width - 10, HUD_TOP, &fps, &right); // Dibuja los FPS
+            layer.draw_text_styled(10, HUD_TOP, &time, &style);
+        }
+        self.framebuffer.draw_layer(&self.hud);
     }
     // Otros métodos según sea necesario
 }
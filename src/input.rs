@@ -0,0 +1,292 @@
+use minifb::{Key, KeyRepeat, MouseMode, Window};
+use std::collections::HashMap;
+
+// Acciones del jugador que se pueden asignar a cualquier tecla
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Action {
+    Forward,
+    Back,
+    StrafeLeft,
+    StrafeRight,
+    TurnLeft,
+    TurnRight,
+    LookUp,
+    LookDown,
+    Run,
+    Jump,
+    Crouch,
+    Map,
+    MapZoomIn,
+    MapZoomOut,
+    MouseLook,
+    Pause,
+}
+
+impl Action {
+    pub const ALL: [Action; 16] = [
+        Action::Forward,
+        Action::Back,
+        Action::StrafeLeft,
+        Action::StrafeRight,
+        Action::TurnLeft,
+        Action::TurnRight,
+        Action::LookUp,
+        Action::LookDown,
+        Action::Run,
+        Action::Jump,
+        Action::Crouch,
+        Action::Map,
+        Action::MapZoomIn,
+        Action::MapZoomOut,
+        Action::MouseLook,
+        Action::Pause,
+    ];
+
+    // Nombre usado en el archivo de configuración
+    pub fn name(self) -> &'static str {
+        match self {
+            Action::Forward => "forward",
+            Action::Back => "back",
+            Action::StrafeLeft => "strafe_left",
+            Action::StrafeRight => "strafe_right",
+            Action::TurnLeft => "turn_left",
+            Action::TurnRight => "turn_right",
+            Action::LookUp => "look_up",
+            Action::LookDown => "look_down",
+            Action::Run => "run",
+            Action::Jump => "jump",
+            Action::Crouch => "crouch",
+            Action::Map => "map",
+            Action::MapZoomIn => "map_zoom_in",
+            Action::MapZoomOut => "map_zoom_out",
+            Action::MouseLook => "mouse_look",
+            Action::Pause => "pause",
+        }
+    }
+
+    fn default_key(self) -> Key {
+        match self {
+            Action::Forward => Key::W,
+            Action::Back => Key::S,
+            Action::StrafeLeft => Key::Q,
+            Action::StrafeRight => Key::E,
+            Action::TurnLeft => Key::A,
+            Action::TurnRight => Key::D,
+            Action::LookUp => Key::Up,
+            Action::LookDown => Key::Down,
+            Action::Run => Key::LeftShift,
+            Action::Jump => Key::Space,
+            Action::Crouch => Key::C,
+            Action::Map => Key::M,
+            Action::MapZoomIn => Key::Period,
+            Action::MapZoomOut => Key::Comma,
+            Action::MouseLook => Key::Y,
+            Action::Pause => Key::P,
+        }
+    }
+}
+
+// Teclas que reconoce el juego y su nombre en la configuración y en las
+// grabaciones de entrada
+const KEY_NAMES: [(Key, &str); 66] = [
+    (Key::A, "A"), (Key::B, "B"), (Key::C, "C"), (Key::D, "D"), (Key::E, "E"),
+    (Key::F, "F"), (Key::G, "G"), (Key::H, "H"), (Key::I, "I"), (Key::J, "J"),
+    (Key::K, "K"), (Key::L, "L"), (Key::M, "M"), (Key::N, "N"), (Key::O, "O"),
+    (Key::P, "P"), (Key::Q, "Q"), (Key::R, "R"), (Key::S, "S"), (Key::T, "T"),
+    (Key::U, "U"), (Key::V, "V"), (Key::W, "W"), (Key::X, "X"), (Key::Y, "Y"),
+    (Key::Z, "Z"),
+    (Key::Key0, "0"), (Key::Key1, "1"), (Key::Key2, "2"), (Key::Key3, "3"),
+    (Key::Key4, "4"), (Key::Key5, "5"), (Key::Key6, "6"), (Key::Key7, "7"),
+    (Key::Key8, "8"), (Key::Key9, "9"),
+    (Key::Up, "Up"), (Key::Down, "Down"), (Key::Left, "Left"), (Key::Right, "Right"),
+    (Key::Space, "Space"), (Key::Tab, "Tab"), (Key::Enter, "Enter"),
+    (Key::Backspace, "Backspace"),
+    (Key::LeftShift, "LeftShift"), (Key::RightShift, "RightShift"),
+    (Key::LeftCtrl, "LeftCtrl"), (Key::RightCtrl, "RightCtrl"),
+    (Key::LeftAlt, "LeftAlt"), (Key::RightAlt, "RightAlt"),
+    (Key::Comma, "Comma"), (Key::Period, "Period"), (Key::Slash, "Slash"),
+    (Key::Semicolon, "Semicolon"), (Key::Apostrophe, "Apostrophe"),
+    (Key::Backslash, "Backslash"),
+    (Key::Escape, "Escape"), (Key::Minus, "Minus"), (Key::Equal, "Equal"),
+    (Key::LeftBracket, "LeftBracket"), (Key::RightBracket, "RightBracket"),
+    (Key::F1, "F1"), (Key::F2, "F2"), (Key::F3, "F3"), (Key::F5, "F5"), (Key::F9, "F9"),
+];
+
+// Teclas que no se pueden asignar a una acción porque el juego ya las usa
+// siempre para lo mismo (menús, atajos de audio, ajustes del mouse, efectos y
+// guardado rápido)
+const RESERVED_KEYS: [Key; 12] = [
+    Key::Escape,
+    Key::N,
+    Key::V,
+    Key::Minus,
+    Key::Equal,
+    Key::LeftBracket,
+    Key::RightBracket,
+    Key::F1,
+    Key::F2,
+    Key::F3,
+    Key::F5,
+    Key::F9,
+];
+
+pub fn is_bindable(key: Key) -> bool {
+    key_name(key).is_some() && !RESERVED_KEYS.contains(&key)
+}
+
+pub fn key_name(key: Key) -> Option<&'static str> {
+    KEY_NAMES.iter().find(|(k, _)| *k == key).map(|(_, name)| *name)
+}
+
+pub fn key_from_name(name: &str) -> Option<Key> {
+    KEY_NAMES
+        .iter()
+        .find(|(_, n)| n.eq_ignore_ascii_case(name))
+        .map(|(key, _)| *key)
+}
+
+// Tecla asignada a cada acción
+#[derive(Clone)]
+pub struct Bindings {
+    keys: HashMap<Action, Key>,
+}
+
+impl Default for Bindings {
+    fn default() -> Self {
+        Bindings {
+            keys: Action::ALL
+                .iter()
+                .map(|&action| (action, action.default_key()))
+                .collect(),
+        }
+    }
+}
+
+impl Bindings {
+    pub fn key(&self, action: Action) -> Key {
+        self.keys[&action]
+    }
+
+    // Asigna la tecla; si otra acción la usaba, esa toma la tecla anterior
+    // para que ninguna quede sin asignar
+    pub fn set(&mut self, action: Action, key: Key) {
+        let previous = self.key(action);
+        if let Some(other) = Action::ALL.iter().find(|&&a| a != action && self.key(a) == key) {
+            self.keys.insert(*other, previous);
+        }
+        self.keys.insert(action, key);
+    }
+
+    pub fn is_down(&self, input: &InputFrame, action: Action) -> bool {
+        input.is_down(self.key(action))
+    }
+
+    pub fn is_pressed(&self, input: &InputFrame, action: Action) -> bool {
+        input.is_pressed(self.key(action), KeyRepeat::No)
+    }
+}
+
+// Estado de la entrada en un cuadro. La lógica del juego lee de aquí y no
+// de la ventana, así una partida se puede grabar y reproducir igual
+#[derive(Clone, Default)]
+pub struct InputFrame {
+    pub steps: usize, // Pasos de simulación que se ejecutan en este cuadro
+    down: Vec<Key>,
+    pressed: Vec<Key>,  // Recién presionadas
+    repeated: Vec<Key>, // Recién presionadas o repetidas por mantenerlas
+    pub mouse: Option<(f32, f32)>,
+    pub width: usize, // Ancho de la ventana, para el giro en los bordes
+}
+
+fn keys_to_text(keys: &[Key]) -> String {
+    keys.iter()
+        .filter_map(|&key| key_name(key))
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+fn keys_from_text(text: &str) -> Result<Vec<Key>, String> {
+    text.split(',')
+        .filter(|name| !name.is_empty())
+        .map(|name| key_from_name(name).ok_or_else(|| format!("unknown key {}", name)))
+        .collect()
+}
+
+impl InputFrame {
+    pub fn from_window(window: &Window, steps: usize) -> Self {
+        InputFrame {
+            steps,
+            down: window.get_keys(),
+            pressed: window.get_keys_pressed(KeyRepeat::No),
+            repeated: window.get_keys_pressed(KeyRepeat::Yes),
+            mouse: window.get_unscaled_mouse_pos(MouseMode::Clamp),
+            width: window.get_size().0,
+        }
+    }
+
+    // Cuadro sin ninguna tecla, para correr sin ventana
+    pub fn idle(steps: usize) -> Self {
+        InputFrame {
+            steps,
+            ..InputFrame::default()
+        }
+    }
+
+    pub fn is_down(&self, key: Key) -> bool {
+        self.down.contains(&key)
+    }
+
+    pub fn is_pressed(&self, key: Key, repeat: KeyRepeat) -> bool {
+        match repeat {
+            KeyRepeat::No => self.pressed.contains(&key),
+            KeyRepeat::Yes => self.repeated.contains(&key),
+        }
+    }
+
+    pub fn pressed_keys(&self) -> &[Key] {
+        &self.pressed
+    }
+
+    // Una línea por cuadro: pasos;abajo;presionadas;repetidas;mouse;ancho
+    pub fn to_line(&self) -> String {
+        let mouse = match self.mouse {
+            Some((x, y)) => format!("{},{}", x, y),
+            None => "-".to_string(),
+        };
+        format!(
+            "{};{};{};{};{};{}",
+            self.steps,
+            keys_to_text(&self.down),
+            keys_to_text(&self.pressed),
+            keys_to_text(&self.repeated),
+            mouse,
+            self.width
+        )
+    }
+
+    pub fn from_line(line: &str) -> Result<Self, String> {
+        let fields: Vec<&str> = line.split(';').collect();
+        let [steps, down, pressed, repeated, mouse, width] = fields[..] else {
+            return Err(format!("expected 6 fields, found {}", fields.len()));
+        };
+
+        let number = |text: &str| {
+            text.parse::<f32>()
+                .map_err(|_| format!("expected a number, found {}", text))
+        };
+        let mouse = match mouse.split_once(',') {
+            Some((x, y)) => Some((number(x)?, number(y)?)),
+            None if mouse == "-" => None,
+            None => return Err(format!("expected x,y or -, found {}", mouse)),
+        };
+
+        Ok(InputFrame {
+            steps: number(steps)? as usize,
+            down: keys_from_text(down)?,
+            pressed: keys_from_text(pressed)?,
+            repeated: keys_from_text(repeated)?,
+            mouse,
+            width: number(width)? as usize,
+        })
+    }
+}
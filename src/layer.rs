@@ -0,0 +1,44 @@
+use crate::framebuffer::{BlendMode, Framebuffer};
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+// Cuadro aparte que se dibuja por su cuenta y después se compone encima
+// del principal. Recuerda de qué dependía su contenido para no volver a
+// dibujarlo si nada cambió
+pub struct Layer {
+    pub framebuffer: Framebuffer,
+    pub x: isize, // Posición en el cuadro principal
+    pub y: isize,
+    pub opacity: f32,
+    pub blend_mode: BlendMode,
+    key: Option<u64>,
+}
+
+impl Layer {
+    pub fn new(width: usize, height: usize) -> Self {
+        let mut framebuffer = Framebuffer::new(width, height);
+        framebuffer.clear_transparent();
+        Layer {
+            framebuffer,
+            x: 0,
+            y: 0,
+            opacity: 1.0,
+            blend_mode: BlendMode::Normal,
+            key: None,
+        }
+    }
+
+    // true si `state` cambió desde el último dibujo; en ese caso la capa
+    // queda vacía para volver a dibujarla
+    pub fn needs_redraw(&mut self, state: impl Hash) -> bool {
+        let mut hasher = DefaultHasher::new();
+        state.hash(&mut hasher);
+        let key = hasher.finish();
+        if self.key == Some(key) {
+            return false;
+        }
+        self.key = Some(key);
+        self.framebuffer.clear_transparent();
+        true
+    }
+}
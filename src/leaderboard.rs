@@ -0,0 +1,125 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+// Mejores tiempos que se guardan por laberinto
+pub const MAX_ENTRIES: usize = 5;
+
+const HEADER: &str = "# maze_runner best times: maze time_in_seconds steps";
+
+// Tiempo y pasos de una partida
+#[derive(Clone, Copy, Default)]
+pub struct Run {
+    pub time: f32, // Segundos
+    pub steps: u32,
+}
+
+// El archivo va junto al de configuración
+pub fn path_for(config_path: &Path) -> PathBuf {
+    config_path.with_file_name("best_times.txt")
+}
+
+// FNV-1a de 64 bits; alcanza para distinguir laberintos, no es criptográfico
+fn fnv1a(bytes: impl IntoIterator<Item = u8>) -> u64 {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for byte in bytes {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    hash
+}
+
+// Clave de un laberinto según su contenido: si se edita el archivo empieza
+// una tabla nueva
+pub fn maze_key(maze: &[Vec<char>]) -> String {
+    let text: String = maze
+        .iter()
+        .map(|row| row.iter().collect::<String>())
+        .collect::<Vec<_>>()
+        .join("\n");
+    format!("maze-{:016x}", fnv1a(text.bytes()))
+}
+
+pub fn seed_key(seed: u64) -> String {
+    format!("seed-{}", seed)
+}
+
+// 83.456 -> "1:23.45"
+pub fn format_time(seconds: f32) -> String {
+    let hundredths = (seconds.max(0.0) * 100.0) as u64;
+    format!("{}:{:02}.{:02}", hundredths / 6000, hundredths / 100 % 60, hundredths % 100)
+}
+
+#[derive(Default)]
+pub struct Leaderboard {
+    boards: BTreeMap<String, Vec<Run>>,
+}
+
+impl Leaderboard {
+    // Si el archivo no existe la tabla empieza vacía; las líneas inválidas
+    // se reportan y se ignoran
+    pub fn load(path: &Path) -> Self {
+        let mut leaderboard = Leaderboard::default();
+        let text = match fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return leaderboard,
+            Err(e) => {
+                eprintln!("{}: {}", path.display(), e);
+                return leaderboard;
+            }
+        };
+
+        for (number, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let run = match fields[..] {
+                [key, time, steps] => match (time.parse::<f32>(), steps.parse::<u32>()) {
+                    (Ok(time), Ok(steps)) if time.is_finite() && time >= 0.0 => Some((key, Run { time, steps })),
+                    _ => None,
+                },
+                _ => None,
+            };
+            match run {
+                Some((key, run)) => {
+                    leaderboard.insert(key, run);
+                }
+                None => eprintln!("{}:{}: expected maze, time and steps", path.display(), number + 1),
+            }
+        }
+        leaderboard
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let mut text = format!("{}\n", HEADER);
+        for (key, runs) in &self.boards {
+            for run in runs {
+                text.push_str(&format!("{} {:.3} {}\n", key, run.time, run.steps));
+            }
+        }
+        fs::write(path, text)
+    }
+
+    pub fn best(&self, key: &str) -> &[Run] {
+        self.boards.get(key).map_or(&[], |runs| runs.as_slice())
+    }
+
+    // Agrega la partida si entra entre los mejores tiempos y devuelve su
+    // posición (0 = el mejor)
+    pub fn insert(&mut self, key: &str, run: Run) -> Option<usize> {
+        let runs = self.boards.entry(key.to_string()).or_default();
+        let rank = runs.iter().position(|other| run.time < other.time).unwrap_or(runs.len());
+        if rank >= MAX_ENTRIES {
+            return None;
+        }
+        runs.insert(rank, run);
+        runs.truncate(MAX_ENTRIES);
+        Some(rank)
+    }
+}
@@ -1,40 +1,295 @@
 use crate::framebuffer::Framebuffer;
-use nalgebra_glm::Vec3;
+use nalgebra_glm::{Vec2, Vec3};
 
+// Cómo se dibuja una línea
+#[derive(Clone, Copy, Debug)]
+pub struct LineStyle {
+    pub thickness: usize,             // Lado en píxeles del pincel cuadrado
+    pub dash: Option<(usize, usize)>, // Píxeles dibujados y saltados, alternando
+}
+
+impl Default for LineStyle {
+    fn default() -> Self {
+        LineStyle {
+            thickness: 1,
+            dash: None,
+        }
+    }
+}
+
+// Las coordenadas pueden ser negativas o caer fuera del cuadro; se recorta
+// antes de dibujar. La z se ignora
 pub trait Line {
     fn line(&mut self, start: Vec3, end: Vec3);
+    fn line_styled(&mut self, start: Vec3, end: Vec3, style: &LineStyle);
+    // Suavizada con el algoritmo de Xiaolin Wu; siempre de un píxel
+    fn line_aa(&mut self, start: Vec3, end: Vec3);
+}
+
+// Códigos de Cohen–Sutherland: de qué lado del rectángulo queda un punto
+const LEFT: u8 = 1;
+const RIGHT: u8 = 2;
+const ABOVE: u8 = 4;
+const BELOW: u8 = 8;
+
+fn outcode(p: Vec2, min: Vec2, max: Vec2) -> u8 {
+    let mut code = 0;
+    if p.x < min.x {
+        code |= LEFT;
+    } else if p.x > max.x {
+        code |= RIGHT;
+    }
+    if p.y < min.y {
+        code |= ABOVE;
+    } else if p.y > max.y {
+        code |= BELOW;
+    }
+    code
+}
+
+// Recorta el segmento al rectángulo entre `min` y `max`; None si queda
+// completamente afuera
+fn clip(mut a: Vec2, mut b: Vec2, min: Vec2, max: Vec2) -> Option<(Vec2, Vec2)> {
+    // Cada vuelta lleva un extremo a un borde, así que alcanzan pocas; el
+    // límite evita quedarse girando por errores de redondeo
+    for _ in 0..8 {
+        let (code_a, code_b) = (outcode(a, min, max), outcode(b, min, max));
+        if code_a | code_b == 0 {
+            return Some((a, b));
+        }
+        if code_a & code_b != 0 {
+            return None;
+        }
+
+        let out = if code_a != 0 { code_a } else { code_b };
+        let p = if out & ABOVE != 0 {
+            Vec2::new(a.x + (b.x - a.x) * (min.y - a.y) / (b.y - a.y), min.y)
+        } else if out & BELOW != 0 {
+            Vec2::new(a.x + (b.x - a.x) * (max.y - a.y) / (b.y - a.y), max.y)
+        } else if out & LEFT != 0 {
+            Vec2::new(min.x, a.y + (b.y - a.y) * (min.x - a.x) / (b.x - a.x))
+        } else {
+            Vec2::new(max.x, a.y + (b.y - a.y) * (max.x - a.x) / (b.x - a.x))
+        };
+        if out == code_a {
+            a = p;
+        } else {
+            b = p;
+        }
+    }
+    None
+}
+
+fn bounds(framebuffer: &Framebuffer) -> Vec2 {
+    Vec2::new(framebuffer.width as f32 - 1.0, framebuffer.height as f32 - 1.0)
 }
 
 impl Line for Framebuffer {
     fn line(&mut self, start: Vec3, end: Vec3) {
-        let dx = (end.x as i32 - start.x as i32).abs();
-        let dy = -(end.y as i32 - start.y as i32).abs();
+        self.line_styled(start, end, &LineStyle::default());
+    }
 
-        let sx = if start.x < end.x { 1 } else { -1 };
-        let sy = if start.y < end.y { 1 } else { -1 };
-        
-        let mut err = dx + dy;
+    fn line_styled(&mut self, start: Vec3, end: Vec3, style: &LineStyle) {
+        if self.width == 0 || self.height == 0 {
+            return;
+        }
+        // Un pincel grueso asoma aunque el centro quede apenas afuera
+        let margin = Vec2::repeat(style.thickness as f32 / 2.0);
+        let Some((a, b)) = clip(start.xy(), end.xy(), -margin, bounds(self) + margin) else {
+            return;
+        };
 
-        let mut current = start.map(|x| x as i32);
+        // Bresenham sobre coordenadas enteras: el paso sale de los enteros
+        // para que siempre se llegue al final
+        let (x1, y1) = (b.x.round() as i32, b.y.round() as i32);
+        let (mut x, mut y) = (a.x.round() as i32, a.y.round() as i32);
+        let dx = (x1 - x).abs();
+        let dy = -(y1 - y).abs();
+        let sx = if x < x1 { 1 } else { -1 };
+        let sy = if y < y1 { 1 } else { -1 };
+        let mut err = dx + dy;
 
+        let thickness = style.thickness.max(1) as i32;
+        let mut step = 0;
         loop {
-            self.point(current.x as usize, current.y as usize);
+            let visible = style.dash.is_none_or(|(on, off)| on + off == 0 || step % (on + off) < on);
+            if visible {
+                for by in 0..thickness {
+                    for bx in 0..thickness {
+                        let (px, py) = (x + bx - thickness / 2, y + by - thickness / 2);
+                        if px >= 0 && py >= 0 {
+                            self.point(px as usize, py as usize);
+                        }
+                    }
+                }
+            }
 
-            if current == end.map(|x| x as i32) {
+            if x == x1 && y == y1 {
                 break;
             }
-
             let e2 = 2 * err;
-            
             if e2 >= dy {
                 err += dy;
-                current.x += sx;
+                x += sx;
             }
-            
             if e2 <= dx {
                 err += dx;
-                current.y += sy;
+                y += sy;
             }
+            step += 1;
         }
     }
-}
\ No newline at end of file
+
+    fn line_aa(&mut self, start: Vec3, end: Vec3) {
+        if self.width == 0 || self.height == 0 {
+            return;
+        }
+        // Una línea a menos de un píxel del borde todavía tiñe el de la orilla
+        let margin = Vec2::repeat(1.0);
+        let Some((mut a, mut b)) = clip(start.xy(), end.xy(), -margin, bounds(self) + margin) else {
+            return;
+        };
+
+        // Se recorre siempre el eje más largo, de izquierda a derecha
+        let steep = (b.y - a.y).abs() > (b.x - a.x).abs();
+        if steep {
+            a = Vec2::new(a.y, a.x);
+            b = Vec2::new(b.y, b.x);
+        }
+        if a.x > b.x {
+            std::mem::swap(&mut a, &mut b);
+        }
+        let dx = b.x - a.x;
+        let gradient = if dx == 0.0 { 0.0 } else { (b.y - a.y) / dx };
+
+        let color = self.current_color();
+        let mut plot = |major: i32, minor: f32, coverage: f32| {
+            let (x, y) = if steep { (minor as i32, major) } else { (major, minor as i32) };
+            if x >= 0 && y >= 0 {
+                self.blend_point(x as usize, y as usize, color, coverage);
+            }
+        };
+
+        // Cada columna reparte la intensidad entre los dos píxeles más
+        // cercanos según qué tan lejos pasa la línea de cada uno
+        let (first, last) = (a.x.round() as i32, b.x.round() as i32);
+        let mut y = a.y + gradient * (first as f32 - a.x);
+        for x in first..=last {
+            let floor = y.floor();
+            let fraction = y - floor;
+            plot(x, floor, 1.0 - fraction);
+            plot(x, floor + 1.0, fraction);
+            y += gradient;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nalgebra_glm::vec3;
+
+    const EMPTY: u32 = 0xFF000000;
+
+    fn lit(framebuffer: &Framebuffer) -> Vec<(usize, usize)> {
+        (0..framebuffer.height)
+            .flat_map(|y| (0..framebuffer.width).map(move |x| (x, y)))
+            .filter(|&(x, y)| framebuffer.buffer[y * framebuffer.width + x] != EMPTY)
+            .collect()
+    }
+
+    fn unit_box() -> (Vec2, Vec2) {
+        (Vec2::zeros(), Vec2::new(9.0, 9.0))
+    }
+
+    #[test]
+    fn clip_rejects_segments_off_screen() {
+        let (min, max) = unit_box();
+        assert_eq!(clip(Vec2::new(-10.0, -10.0), Vec2::new(-5.0, 20.0), min, max), None);
+        assert_eq!(clip(Vec2::new(12.0, 3.0), Vec2::new(30.0, -4.0), min, max), None);
+        // Pasa junto a la esquina sin tocar el cuadro
+        assert_eq!(clip(Vec2::new(-3.0, 1.0), Vec2::new(1.0, -3.0), min, max), None);
+    }
+
+    #[test]
+    fn clip_cuts_a_segment_across_a_corner() {
+        let (min, max) = unit_box();
+        let clipped = clip(Vec2::new(-2.0, 3.0), Vec2::new(3.0, -2.0), min, max);
+        assert_eq!(clipped, Some((Vec2::new(0.0, 1.0), Vec2::new(1.0, 0.0))));
+    }
+
+    #[test]
+    fn clip_keeps_zero_length_segments_inside() {
+        let (min, max) = unit_box();
+        let p = Vec2::new(4.0, 5.0);
+        assert_eq!(clip(p, p, min, max), Some((p, p)));
+        assert_eq!(clip(Vec2::new(-1.0, 5.0), Vec2::new(-1.0, 5.0), min, max), None);
+    }
+
+    #[test]
+    fn line_with_negative_coordinates_draws_the_visible_part() {
+        let mut framebuffer = Framebuffer::new(10, 10);
+        framebuffer.line(vec3(-20.0, 5.0, 0.0), vec3(30.0, 5.0, 0.0));
+        let expected: Vec<_> = (0..10).map(|x| (x, 5)).collect();
+        assert_eq!(lit(&framebuffer), expected);
+    }
+
+    #[test]
+    fn thick_line_just_outside_still_shows() {
+        let mut framebuffer = Framebuffer::new(10, 10);
+        let style = LineStyle { thickness: 3, dash: None };
+        framebuffer.line_styled(vec3(-5.0, -1.0, 0.0), vec3(15.0, -1.0, 0.0), &style);
+        let expected: Vec<_> = (0..10).map(|x| (x, 0)).collect();
+        assert_eq!(lit(&framebuffer), expected);
+    }
+
+    #[test]
+    fn zero_length_line_draws_one_pixel() {
+        let mut framebuffer = Framebuffer::new(10, 10);
+        framebuffer.line(vec3(3.0, 7.0, 0.0), vec3(3.0, 7.0, 0.0));
+        assert_eq!(lit(&framebuffer), vec![(3, 7)]);
+
+        let mut framebuffer = Framebuffer::new(10, 10);
+        framebuffer.line_aa(vec3(3.0, 7.0, 0.0), vec3(3.0, 7.0, 0.0));
+        assert_eq!(lit(&framebuffer), vec![(3, 7)]);
+    }
+
+    #[test]
+    fn dash_pattern_repeats_along_the_line() {
+        let mut framebuffer = Framebuffer::new(10, 1);
+        let style = LineStyle { thickness: 1, dash: Some((2, 1)) };
+        framebuffer.line_styled(vec3(0.0, 0.0, 0.0), vec3(9.0, 0.0, 0.0), &style);
+        let expected: Vec<_> = [0, 1, 3, 4, 6, 7, 9].iter().map(|&x| (x, 0)).collect();
+        assert_eq!(lit(&framebuffer), expected);
+
+        // Un patrón vacío dibuja la línea entera
+        let mut framebuffer = Framebuffer::new(10, 1);
+        let style = LineStyle { thickness: 1, dash: Some((0, 0)) };
+        framebuffer.line_styled(vec3(0.0, 0.0, 0.0), vec3(9.0, 0.0, 0.0), &style);
+        assert_eq!(lit(&framebuffer).len(), 10);
+    }
+
+    #[test]
+    fn antialiased_line_stays_inside_the_framebuffer() {
+        let mut framebuffer = Framebuffer::new(10, 10);
+        framebuffer.line_aa(vec3(-5.0, 4.0, 0.0), vec3(20.0, 4.0, 0.0));
+        let expected: Vec<_> = (0..10).map(|x| (x, 4)).collect();
+        assert_eq!(lit(&framebuffer), expected);
+
+        // Pegadas a los bordes o con extremos muy lejos: la intensidad que
+        // cae afuera se pierde en vez de pasar a la fila o columna siguiente
+        let mut framebuffer = Framebuffer::new(10, 10);
+        framebuffer.line_aa(vec3(9.6, -3.0, 0.0), vec3(9.6, 14.0, 0.0));
+        assert_eq!(lit(&framebuffer), (0..10).map(|y| (9, y)).collect::<Vec<_>>());
+
+        let mut framebuffer = Framebuffer::new(10, 10);
+        framebuffer.line_aa(vec3(-1.0e6, 9.9, 0.0), vec3(1.0e6, 9.9, 0.0));
+        assert_eq!(lit(&framebuffer), (0..10).map(|x| (x, 9)).collect::<Vec<_>>());
+
+        let mut framebuffer = Framebuffer::new(10, 10);
+        framebuffer.line_aa(vec3(2.2, 1.0e6, 0.0), vec3(2.2, -1.0e6, 0.0));
+        let pixels = lit(&framebuffer);
+        assert_eq!(pixels.len(), 20);
+        assert!(pixels.iter().all(|&(x, _)| x == 2 || x == 3));
+    }
+}
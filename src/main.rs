@@ -1,19 +1,117 @@
+mod audio_backend;
+mod bitmap;
 mod caster;
+mod cli;
+mod clock;
+mod config;
+mod emitter;
+mod font;
 mod framebuffer;
+mod input;
+mod layer;
+mod leaderboard;
+mod line;
 mod maze;
+mod minimap;
+mod music;
 mod player;
+mod replay;
+mod rng;
+mod save;
+mod scene;
+mod settings;
 mod sfx;
+mod sound_cache;
+mod synth;
+mod ttf;
 mod game; // Asumiendo que tienes un archivo separado para Framebuffer
 
+use maze::{find_start_position, load_maze};
+use replay::{Recorder, Replay};
+use std::process;
+
+fn usage_error(message: &str) -> ! {
+    eprintln!("error: {}\n{}", message, cli::USAGE);
+    process::exit(2);
+}
+
 fn main() {
-    let mut game = game::Game::new();
+    let options = match cli::parse(std::env::args().skip(1)) {
+        Ok(cli::Command::Run(options)) => options,
+        Ok(cli::Command::Help) => {
+            println!("{}", cli::USAGE);
+            return;
+        }
+        Err(e) => usage_error(&e),
+    };
 
-    while game.window.is_open() {
-        game.render();
+    let config_path = options.config.clone().unwrap_or_else(config::default_path);
+    let mut config = config::Config::load(&config_path);
+    for assignment in &options.overrides {
+        if let Err(e) = config.apply_override(assignment) {
+            usage_error(&format!("--set {}: {}", assignment, e));
+        }
+    }
+    if options.window_scale.is_some() {
+        config.settings.window_scale = options.window_scale;
+    }
+
+    // El laberinto se revisa antes de abrir la ventana
+    let level = if let Some(path) = &options.maze {
+        match load_maze(path) {
+            Ok(maze) if find_start_position(&maze.cells, config.render.block_size).is_some() => {}
+            Ok(_) => usage_error(&format!("--maze {}: no start position ('s')", path.display())),
+            Err(e) => usage_error(&format!("--maze {}: {}", path.display(), e)),
+        }
+        // La ruta completa para que la partida guardada funcione desde otro directorio
+        Some(game::Level::File(std::fs::canonicalize(path).unwrap_or_else(|_| path.clone())))
+    } else {
+        options.seed.map(game::Level::Generated)
+    };
 
-        // Verifica el estado del juego y sale si es necesario
-        if let game::GameState::Exiting = game.state {
+    let record = options.record.as_ref().map(|path| {
+        Recorder::create(path)
+            .unwrap_or_else(|e| usage_error(&format!("--record {}: {}", path.display(), e)))
+    });
+    let replay = options.replay.as_ref().map(|path| {
+        Replay::open(path)
+            .unwrap_or_else(|e| usage_error(&format!("--replay {}: {}", path.display(), e)))
+    });
+
+    let launch = game::Launch {
+        headless: options.headless,
+        record,
+        replay,
+    };
+    let mut game = game::Game::new(config, config_path, launch);
+    if options.mute {
+        game.audio.toggle_mute();
+    }
+
+    // Con un laberinto en la línea de comandos se salta el título
+    let first = match level {
+        Some(level) => {
+            game.level = level;
+            scene::SceneId::Playing
+        }
+        None => scene::SceneId::Title,
+    };
+    let mut scenes = scene::SceneStack::new(&mut game, first);
+
+    let mut frames = 0;
+    while game.is_open() && scenes.is_running() {
+        if options.frames.is_some_and(|limit| frames >= limit) || !game.begin_frame() {
             break;
         }
+        scenes.frame(&mut game);
+        frames += 1;
+    }
+
+    // Solo hay registro con MAZE_AUDIO=record, con o sin ventana; sin esa
+    // variable --headless es silencioso y no anota nada
+    if let Some(log) = &game.audio_log {
+        for event in log.events() {
+            eprintln!("{}", event);
+        }
     }
 }
@@ -1,23 +1,257 @@
-
+use crate::rng::Rng;
 use std::fs::File;
-use std::io::{BufRead, BufReader};
+use std::io::{self, BufRead, BufReader};
+use std::path::Path;
+
+// Qué tanto del laberinto muestra el mapa
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum MapMode {
+    Always,   // Todo el laberinto desde el inicio
+    Explored, // Solo lo que el jugador ya vio
+    Hidden,   // Sin mapa
+}
+
+pub struct MazeFile {
+    pub cells: Vec<Vec<char>>,
+    pub map: MapMode,
+}
+
+// Las líneas que empiezan con '#' no son parte del laberinto: son
+// comentarios u opciones del nivel como `# map = explored`. Las opciones
+// que no se conocen se ignoran, igual que cualquier otro comentario
+fn parse_header(line: &str, maze: &mut MazeFile) -> Result<(), String> {
+    let Some((key, value)) = line.trim_start_matches('#').split_once('=') else {
+        return Ok(());
+    };
+    match (key.trim(), value.trim()) {
+        ("map", "always") => maze.map = MapMode::Always,
+        ("map", "explored") => maze.map = MapMode::Explored,
+        ("map", "none") => maze.map = MapMode::Hidden,
+        ("map", other) => return Err(format!("unknown map mode {} (expected always, explored or none)", other)),
+        _ => {}
+    }
+    Ok(())
+}
 
-pub fn load_maze(filename: &str) -> Vec<Vec<char>> {
-    let file = File::open(filename).unwrap();
+pub fn load_maze(filename: &Path) -> io::Result<MazeFile> {
+    let file = File::open(filename)?;
 
     let reader = BufReader::new(file);
+    let mut maze = MazeFile {
+        cells: Vec::new(),
+        map: MapMode::Explored,
+    };
+
+    for (number, line) in reader.lines().enumerate() {
+        let line = line?;
+        if line.starts_with('#') {
+            parse_header(&line, &mut maze).map_err(|e| {
+                io::Error::new(io::ErrorKind::InvalidData, format!("line {}: {}", number + 1, e))
+            })?;
+        } else {
+            maze.cells.push(line.chars().collect());
+        }
+    }
+
+    // Las líneas vacías al final del archivo no son filas
+    while maze.cells.last().is_some_and(|row| row.is_empty()) {
+        maze.cells.pop();
+    }
+    check_cells(&maze.cells).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    Ok(maze)
+}
+
+// Todas las filas del mismo ancho y el borde cerrado, para que ni el
+// jugador ni los rayos salgan del laberinto
+fn check_cells(cells: &[Vec<char>]) -> Result<(), String> {
+    let width = cells.first().map_or(0, Vec::len);
+    if width == 0 {
+        return Err("the maze is empty".to_string());
+    }
+    for (row, row_data) in cells.iter().enumerate() {
+        if row_data.len() != width {
+            return Err(format!(
+                "row {} is {} cells wide, expected {} like the first row",
+                row + 1,
+                row_data.len(),
+                width
+            ));
+        }
+        let on_border = |col: usize| row == 0 || row == cells.len() - 1 || col == 0 || col == width - 1;
+        if let Some(col) = (0..width).find(|&col| on_border(col) && is_walkable(row_data[col])) {
+            return Err(format!(
+                "'{}' at row {}, column {} leaves the border open",
+                row_data[col],
+                row + 1,
+                col + 1
+            ));
+        }
+    }
+    Ok(())
+}
+
+// Laberinto de `width` x `height` celdas con el mismo formato de los
+// archivos: cada celda ocupa dos columnas y las paredes una. Se recorre con
+// backtracking desde una esquina y la salida queda en la celda más lejana
+pub fn generate_maze(seed: u64, width: usize, height: usize) -> Vec<Vec<char>> {
+    let mut rng = Rng::new(seed);
+    let mut maze = vec![vec![' '; 3 * width + 1]; 2 * height + 1];
+    for (row, line) in maze.iter_mut().enumerate() {
+        for (col, cell) in line.iter_mut().enumerate() {
+            *cell = match (row % 2 == 0, col % 3 == 0) {
+                (true, true) => '+',
+                (true, false) => '-',
+                (false, true) => '|',
+                (false, false) => ' ',
+            };
+        }
+    }
+
+    let mut visited = vec![vec![false; width]; height];
+    let mut stack = vec![(0, 0)];
+    let mut farthest = ((0, 0), 0);
+    visited[0][0] = true;
+
+    while let Some(&(cx, cy)) = stack.last() {
+        let neighbors: Vec<(usize, usize)> = [(0, -1), (1, 0), (0, 1), (-1, 0)]
+            .iter()
+            .map(|&(dx, dy)| (cx as i32 + dx, cy as i32 + dy))
+            .filter(|&(x, y)| x >= 0 && y >= 0 && (x as usize) < width && (y as usize) < height)
+            .map(|(x, y)| (x as usize, y as usize))
+            .filter(|&(x, y)| !visited[y][x])
+            .collect();
+
+        if neighbors.is_empty() {
+            stack.pop();
+            continue;
+        }
+
+        // Abre la pared entre la celda actual y la vecina elegida
+        let (nx, ny) = neighbors[rng.below(neighbors.len())];
+        if nx != cx {
+            maze[2 * cy + 1][3 * cx.max(nx)] = ' ';
+        } else {
+            let row = 2 * cy.max(ny);
+            maze[row][3 * cx + 1] = ' ';
+            maze[row][3 * cx + 2] = ' ';
+        }
+        visited[ny][nx] = true;
+        stack.push((nx, ny));
+
+        // En un laberinto sin ciclos la profundidad es la distancia
+        if stack.len() > farthest.1 {
+            farthest = ((nx, ny), stack.len());
+        }
+    }
+
+    // Inicio en la primera celda; find_start_position centra al jugador en
+    // el bloque de la 's'
+    maze[1][2] = 's';
+    let ((gx, gy), _) = farthest;
+    maze[2 * gy + 1][3 * gx + 1] = 'g';
+    maze
+}
+
+// Ángulo hacia el lado con el pasillo libre más largo desde (col, row),
+// para que el jugador no empiece mirando una pared
+pub fn open_direction(maze: &[Vec<char>], col: usize, row: usize) -> f32 {
+    use std::f32::consts::PI;
+    let directions = [(1, 0, 0.0), (0, 1, PI / 2.0), (-1, 0, PI), (0, -1, -PI / 2.0)];
+
+    let free_run = |dx: i32, dy: i32| {
+        let (mut x, mut y) = (col as i32, row as i32);
+        let mut run = 0;
+        loop {
+            x += dx;
+            y += dy;
+            let cell = (x >= 0 && y >= 0)
+                .then(|| maze.get(y as usize).and_then(|line| line.get(x as usize)))
+                .flatten();
+            if !cell.is_some_and(|&cell| is_walkable(cell)) {
+                return run;
+            }
+            run += 1;
+        }
+    };
+
+    directions
+        .iter()
+        .max_by_key(|(dx, dy, _)| free_run(*dx, *dy))
+        .map_or(0.0, |&(_, _, angle)| angle)
+}
+
+// Altura del techo de un pasadizo '=' como fracción de la altura de pared
+pub const CRAWL_CEILING: f32 = 0.5;
+
+// Celdas que no son pared sólida: los rayos las atraviesan y el jugador
+// puede entrar si cabe bajo su techo
+pub fn is_walkable(cell: char) -> bool {
+    matches!(cell, ' ' | 's' | 'g' | '=' | 'd')
+}
+
+// Pasadizo de techo bajo, solo se puede cruzar agachado
+pub fn is_crawlspace(cell: char) -> bool {
+    cell == '='
+}
+
+// Altura del techo de una celda como fracción de la altura de pared
+pub fn ceiling_height(cell: char) -> f32 {
+    if is_crawlspace(cell) {
+        CRAWL_CEILING
+    } else {
+        1.0
+    }
+}
+
+// Material del piso de una celda, decide cómo suenan las pisadas
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Floor {
+    Stone,
+    Dirt,
+    Water,
+}
+
+pub fn floor_material(cell: char) -> Floor {
+    match cell {
+        '=' => Floor::Dirt,
+        'd' => Floor::Water,
+        _ => Floor::Stone,
+    }
+}
+
+// Fracción de celdas libres en un cuadro de 5x5 alrededor de (col, row);
+// lo de fuera del laberinto cuenta como pared
+pub fn openness(maze: &[Vec<char>], col: usize, row: usize) -> f32 {
+    const RADIUS: i32 = 2;
+    let mut open = 0;
+    let mut total = 0;
+
+    for dy in -RADIUS..=RADIUS {
+        for dx in -RADIUS..=RADIUS {
+            total += 1;
+            let x = col as i32 + dx;
+            let y = row as i32 + dy;
+            if x < 0 || y < 0 {
+                continue;
+            }
+            let cell = maze
+                .get(y as usize)
+                .and_then(|row| row.get(x as usize))
+                .copied();
+            if cell.is_some_and(is_walkable) {
+                open += 1;
+            }
+        }
+    }
 
-    reader
-        .lines()
-        .map(|line| line.unwrap().chars().collect())
-        .collect()
+    open as f32 / total as f32
 }
 
-pub fn find_start_position(maze: &Vec<Vec<char>>, block_size: usize) -> Option<(usize, usize)> {
+pub fn find_start_position(maze: &[Vec<char>], block_size: usize) -> Option<(usize, usize)> {
     for (row, row_data) in maze.iter().enumerate() {
         for (col, &cell) in row_data.iter().enumerate() {
             if cell == 's' {
-                let x = col * block_size - block_size / 2;
+                let x = col * block_size + block_size / 2;
                 let y = row * block_size + block_size / 2;
                 return Some((x, y));
             }
@@ -0,0 +1,288 @@
+use crate::framebuffer::{BlendMode, Framebuffer};
+use crate::layer::Layer;
+use crate::line::{Line, LineStyle};
+use crate::player::Player;
+use nalgebra_glm::{Vec2, Vec3};
+use std::collections::VecDeque;
+
+const RADIUS: f32 = 40.0; // Medio ancho de la ventana del mapa en píxeles
+const MARGIN: f32 = 6.0;
+const PADDING: f32 = 3.0; // Lugar en la capa para el borde y el halo de la salida
+const OPACITY: f32 = 0.85; // El fondo del mapa deja ver un poco la vista
+const GOAL_GLOW: f32 = 4.0; // Radio del halo de la salida en píxeles
+const MIN_ZOOM: f32 = 2.0; // Píxeles por celda
+const MAX_ZOOM: f32 = 16.0;
+const ZOOM_STEP: f32 = 1.25;
+const TRAIL_LENGTH: usize = 200;
+const TRAIL_SPACING: f32 = 0.5; // En celdas
+
+const BORDER_COLOR: u32 = 0xb8b0a0;
+const FLOOR_COLOR: u32 = 0x1b1a19;
+const UNKNOWN_COLOR: u32 = 0x0a0a0a;
+const TRAIL_COLOR: u32 = 0x8a7f6a;
+const GOAL_COLOR: u32 = 0xffbf00;
+const ARROW_COLOR: u32 = 0xffffff;
+const FOV_COLOR: u32 = 0x6a6458;
+const FOV_LENGTH: f32 = RADIUS * 0.7; // Largo en píxeles de los bordes del campo de visión
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum MinimapShape {
+    Circle,
+    Square,
+}
+
+impl MinimapShape {
+    pub fn name(self) -> &'static str {
+        match self {
+            MinimapShape::Circle => "circle",
+            MinimapShape::Square => "square",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        [MinimapShape::Circle, MinimapShape::Square]
+            .into_iter()
+            .find(|shape| shape.name() == name)
+    }
+}
+
+// Color de cada tipo de celda en el mapa; None para el piso
+pub fn cell_color(cell: char) -> Option<u32> {
+    match cell {
+        '+' => Some(0x011f4b),
+        '-' => Some(0x005b96),
+        '|' => Some(0xb3cde0),
+        'g' => Some(GOAL_COLOR),
+        '=' => Some(0x6b4f2a),
+        _ => None,
+    }
+}
+
+// Mapa de la esquina centrado en el jugador, con el rastro de por dónde
+// pasó y la salida si ya se conoce. Se dibuja en su propia capa, que solo
+// se actualiza cuando algo cambió
+pub struct Minimap {
+    pub zoom: f32,
+    trail: VecDeque<Vec2>, // Posiciones del mundo, la más reciente al final
+    goal: Option<(usize, usize)>,
+    layer: Layer,
+}
+
+// Cómo se dibuja: forma, si gira con el jugador y qué celdas se conocen
+pub struct MinimapView<'a> {
+    pub shape: MinimapShape,
+    pub rotate: bool,
+    pub maze: &'a [Vec<char>],
+    pub explored: Option<&'a [Vec<bool>]>, // None = se conoce todo
+    pub block_size: usize,
+}
+
+impl Minimap {
+    pub fn new(zoom: f32) -> Self {
+        let size = ((RADIUS + PADDING) * 2.0) as usize;
+        Minimap {
+            zoom: zoom.clamp(MIN_ZOOM, MAX_ZOOM),
+            trail: VecDeque::new(),
+            goal: None,
+            layer: Layer::new(size, size),
+        }
+    }
+
+    // Al empezar un laberinto se borra el rastro y se busca la salida
+    pub fn reset(&mut self, maze: &[Vec<char>]) {
+        self.trail.clear();
+        self.goal = maze.iter().enumerate().find_map(|(row, cells)| {
+            cells.iter().position(|&cell| cell == 'g').map(|col| (col, row))
+        });
+    }
+
+    pub fn adjust_zoom(&mut self, steps: f32) {
+        self.zoom = (self.zoom * ZOOM_STEP.powf(steps)).clamp(MIN_ZOOM, MAX_ZOOM);
+    }
+
+    // Agrega un punto al rastro cuando el jugador se alejó lo suficiente
+    // del anterior
+    pub fn update(&mut self, pos: Vec2, block_size: usize) {
+        let spacing = TRAIL_SPACING * block_size as f32;
+        if self.trail.back().is_none_or(|last| (pos - last).norm() >= spacing) {
+            self.trail.push_back(pos);
+            if self.trail.len() > TRAIL_LENGTH {
+                self.trail.pop_front();
+            }
+        }
+    }
+
+    pub fn draw(&mut self, target: &mut Framebuffer, player: &Player, view: &MinimapView) {
+        let explored = view.explored.map_or(0, |seen| seen.iter().flatten().filter(|&&seen| seen).count());
+        let state = (
+            [player.pos.x, player.pos.y, player.a, player.fov, self.zoom].map(f32::to_bits),
+            self.trail.len(),
+            explored,
+            view.shape,
+            view.rotate,
+            view.block_size,
+        );
+        if self.layer.needs_redraw(state) {
+            self.render(player, view);
+        }
+
+        self.layer.x = (MARGIN - PADDING) as isize;
+        self.layer.y = (target.height as f32 - MARGIN - RADIUS * 2.0 - PADDING) as isize;
+        target.draw_layer(&self.layer);
+    }
+
+    fn render(&mut self, player: &Player, view: &MinimapView) {
+        let framebuffer = &mut self.layer.framebuffer;
+        let center = Vec2::new(RADIUS + PADDING, RADIUS + PADDING);
+        let block = view.block_size as f32;
+        let scale = self.zoom / block; // Píxeles del mapa por unidad del mundo
+
+        // Ejes de la pantalla en el mundo: con rotación, arriba es hacia
+        // donde mira el jugador
+        let (right, down) = if view.rotate {
+            let forward = Vec2::new(player.a.cos(), player.a.sin());
+            (Vec2::new(-forward.y, forward.x), -forward)
+        } else {
+            (Vec2::new(1.0, 0.0), Vec2::new(0.0, 1.0))
+        };
+        let to_screen = |world: Vec2| {
+            let delta = (world - player.pos) * scale;
+            center + Vec2::new(delta.dot(&right), delta.dot(&down))
+        };
+        let inside = |offset: Vec2| match view.shape {
+            MinimapShape::Circle => offset.norm() <= RADIUS,
+            MinimapShape::Square => offset.x.abs() <= RADIUS && offset.y.abs() <= RADIUS,
+        };
+        let known = |col: usize, row: usize| view.explored.is_none_or(|seen| seen[row][col]);
+
+        // Celdas, pixel por pixel para poder girar
+        framebuffer.set_opacity(OPACITY);
+        let radius = RADIUS as i32;
+        for sy in -radius..=radius {
+            for sx in -radius..=radius {
+                let offset = Vec2::new(sx as f32, sy as f32);
+                if !inside(offset) {
+                    continue;
+                }
+                let world = player.pos + (right * offset.x + down * offset.y) / scale;
+                let cell = (world.x >= 0.0 && world.y >= 0.0)
+                    .then(|| {
+                        let col = (world.x / block) as usize;
+                        let row = (world.y / block) as usize;
+                        let cell = view.maze.get(row).and_then(|cells| cells.get(col))?;
+                        Some((*cell, known(col, row)))
+                    })
+                    .flatten();
+                let color = match cell {
+                    Some((cell, true)) => cell_color(cell).unwrap_or(FLOOR_COLOR),
+                    _ => UNKNOWN_COLOR,
+                };
+                framebuffer.set_current_color(color);
+                framebuffer.point((center.x + offset.x) as usize, (center.y + offset.y) as usize);
+            }
+        }
+
+        framebuffer.set_opacity(1.0);
+
+        // Borde
+        framebuffer.set_current_color(BORDER_COLOR);
+        match view.shape {
+            MinimapShape::Circle => {
+                let steps = (RADIUS * std::f32::consts::TAU) as usize;
+                for i in 0..steps {
+                    let t = i as f32 / steps as f32 * std::f32::consts::TAU;
+                    let point = center + Vec2::new(t.cos(), t.sin()) * (RADIUS + 1.0);
+                    framebuffer.point(point.x as usize, point.y as usize);
+                }
+            }
+            MinimapShape::Square => {
+                let corner = |x: f32, y: f32| Vec3::new(center.x + x * (RADIUS + 1.0), center.y + y * (RADIUS + 1.0), 0.0);
+                let corners = [corner(-1.0, -1.0), corner(1.0, -1.0), corner(1.0, 1.0), corner(-1.0, 1.0)];
+                for i in 0..corners.len() {
+                    framebuffer.line(corners[i], corners[(i + 1) % corners.len()]);
+                }
+            }
+        }
+
+        // Rastro
+        framebuffer.set_current_color(TRAIL_COLOR);
+        for &pos in &self.trail {
+            let point = to_screen(pos);
+            if inside(point - center) {
+                framebuffer.point(point.x as usize, point.y as usize);
+            }
+        }
+
+        // Bordes del campo de visión, punteados
+        framebuffer.set_current_color(FOV_COLOR);
+        let dashed = LineStyle { dash: Some((2, 2)), ..LineStyle::default() };
+        let length = FOV_LENGTH / scale;
+        for side in [-0.5, 0.5] {
+            let angle = player.a + side * player.fov;
+            let end = to_screen(player.pos + Vec2::new(angle.cos(), angle.sin()) * length);
+            framebuffer.line_styled(Vec3::new(center.x, center.y, 0.0), Vec3::new(end.x, end.y, 0.0), &dashed);
+        }
+
+        // Salida: si queda fuera de la ventana se marca en el borde, en su
+        // dirección
+        if let Some((col, row)) = self.goal.filter(|&(col, row)| known(col, row)) {
+            let goal = Vec2::new((col as f32 + 0.5) * block, (row as f32 + 0.5) * block);
+            let mut offset = to_screen(goal) - center;
+            if !inside(offset) {
+                offset *= (RADIUS - 2.0)
+                    / match view.shape {
+                        MinimapShape::Circle => offset.norm(),
+                        MinimapShape::Square => offset.x.abs().max(offset.y.abs()),
+                    };
+            }
+            let point = center + offset;
+
+            // Halo sumado encima para que la salida brille
+            framebuffer.set_blend_mode(BlendMode::Add);
+            let glow = GOAL_GLOW as i32;
+            for dy in -glow..=glow {
+                for dx in -glow..=glow {
+                    let distance = ((dx * dx + dy * dy) as f32).sqrt();
+                    let alpha = (1.0 - distance / GOAL_GLOW) * 0.6;
+                    framebuffer.blend_point((point.x + dx as f32) as usize, (point.y + dy as f32) as usize, GOAL_COLOR, alpha);
+                }
+            }
+            framebuffer.set_blend_mode(BlendMode::Normal);
+
+            framebuffer.set_current_color(GOAL_COLOR);
+            for dy in 0..3 {
+                for dx in 0..3 {
+                    framebuffer.point((point.x + dx as f32 - 1.0) as usize, (point.y + dy as f32 - 1.0) as usize);
+                }
+            }
+        }
+
+        draw_arrow(framebuffer, center, player.a, view.rotate);
+    }
+}
+
+// Triángulo en el centro que apunta hacia donde mira el jugador
+fn draw_arrow(framebuffer: &mut Framebuffer, center: Vec2, angle: f32, rotate: bool) {
+    let direction = if rotate {
+        Vec2::new(0.0, -1.0)
+    } else {
+        Vec2::new(angle.cos(), angle.sin())
+    };
+    let side = Vec2::new(-direction.y, direction.x);
+    let tip = center + direction * 5.0;
+    let left = center - direction * 3.0 + side * 3.0;
+    let right = center - direction * 3.0 - side * 3.0;
+
+    // Un punto está dentro si queda del mismo lado de las tres aristas
+    let edge = |a: Vec2, b: Vec2, p: Vec2| (b.x - a.x) * (p.y - a.y) - (b.y - a.y) * (p.x - a.x);
+    framebuffer.set_current_color(ARROW_COLOR);
+    for y in -6..=6 {
+        for x in -6..=6 {
+            let p = center + Vec2::new(x as f32, y as f32);
+            let sides = [edge(tip, left, p), edge(left, right, p), edge(right, tip, p)];
+            if sides.iter().all(|&s| s >= 0.0) || sides.iter().all(|&s| s <= 0.0) {
+                framebuffer.point(p.x as usize, p.y as usize);
+            }
+        }
+    }
+}
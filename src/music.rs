@@ -0,0 +1,127 @@
+use crate::rng::Rng;
+use crate::sfx::{AudioManager, Channel, SoundHandle};
+
+const CROSSFADE_SECONDS: f32 = 1.5;
+const DUCK_GAIN: f32 = 0.35; // Volumen de la música mientras suena la interfaz
+const DUCK_SPEED: f32 = 4.0; // Cambio de volumen por segundo al bajar o subir
+
+struct Track {
+    name: &'static str,
+    handle: SoundHandle,
+    fade: f32, // 0 = en silencio, 1 = volumen completo
+}
+
+// Lleva la pista de música actual: cambia de pista con un fundido cruzado,
+// puede tocar una lista en orden aleatorio y baja el volumen mientras suenan
+// sonidos de interfaz
+pub struct MusicPlayer {
+    current: Option<Track>,
+    fading_out: Vec<Track>,
+    playlist: Vec<&'static str>, // Vacía cuando se toca una sola pista
+    rng: Rng,
+    duck: f32,
+}
+
+impl MusicPlayer {
+    pub fn new() -> Self {
+        MusicPlayer {
+            current: None,
+            fading_out: Vec::new(),
+            playlist: Vec::new(),
+            rng: Rng::from_time(),
+            duck: 1.0,
+        }
+    }
+
+    // Cambia a una pista; si ya está sonando no la reinicia
+    pub fn play(&mut self, audio: &mut AudioManager, name: &'static str, looped: bool) {
+        self.playlist.clear();
+        if self.current.as_ref().is_some_and(|track| track.name == name) {
+            return;
+        }
+        self.switch_to(audio, name, looped);
+    }
+
+    // Toca las pistas en orden aleatorio, una tras otra
+    pub fn play_shuffled(&mut self, audio: &mut AudioManager, tracks: &[&'static str]) {
+        self.playlist = tracks.to_vec();
+        if let Some(name) = self.next_in_playlist() {
+            self.switch_to(audio, name, false);
+        }
+    }
+
+    pub fn update(&mut self, audio: &mut AudioManager, dt: f32) {
+        let duck_target = if audio.channel_active(Channel::Ui) { DUCK_GAIN } else { 1.0 };
+        if self.duck < duck_target {
+            self.duck = (self.duck + DUCK_SPEED * dt).min(duck_target);
+        } else {
+            self.duck = (self.duck - DUCK_SPEED * dt).max(duck_target);
+        }
+
+        let fade_step = dt / CROSSFADE_SECONDS;
+
+        // Al terminar una pista de la lista entra la siguiente. Se espera a
+        // que la pista haya entrado por completo para no saltar en cada
+        // cuadro cuando no hay dispositivo de audio
+        let finished = self
+            .current
+            .as_ref()
+            .is_some_and(|track| track.fade >= 1.0 && !audio.is_playing(track.handle));
+        if finished && !self.playlist.is_empty() {
+            if let Some(name) = self.next_in_playlist() {
+                self.switch_to(audio, name, false);
+            }
+        }
+
+        if let Some(track) = &mut self.current {
+            track.fade = (track.fade + fade_step).min(1.0);
+            audio.set_gain(track.handle, track.fade * self.duck);
+        }
+
+        let duck = self.duck;
+        self.fading_out.retain_mut(|track| {
+            track.fade -= fade_step;
+            if track.fade <= 0.0 {
+                audio.stop(track.handle);
+                false
+            } else {
+                audio.set_gain(track.handle, track.fade * duck);
+                true
+            }
+        });
+    }
+
+    fn switch_to(&mut self, audio: &mut AudioManager, name: &'static str, looped: bool) {
+        if let Some(old) = self.current.take() {
+            self.fading_out.push(old);
+        }
+
+        match audio.play_stream(Channel::Music, name, looped) {
+            Ok(handle) => {
+                audio.set_gain(handle, 0.0);
+                self.current = Some(Track {
+                    name,
+                    handle,
+                    fade: 0.0,
+                });
+            }
+            Err(e) => eprintln!("{}", e),
+        }
+    }
+
+    // Elige al azar evitando repetir la pista que acaba de sonar
+    fn next_in_playlist(&mut self) -> Option<&'static str> {
+        let last = self.current.as_ref().map(|track| track.name);
+        let candidates: Vec<&'static str> = self
+            .playlist
+            .iter()
+            .copied()
+            .filter(|&name| self.playlist.len() == 1 || Some(name) != last)
+            .collect();
+
+        if candidates.is_empty() {
+            return None;
+        }
+        Some(candidates[self.rng.below(candidates.len())])
+    }
+}
@@ -1,10 +1,25 @@
-use rodio::{Decoder, OutputStream, Sink, Source};
-use std::io::BufReader;
-use std::fs::File;
-use std::sync::{Arc, Mutex};
-use std::thread;
 use nalgebra_glm::Vec2;
-use minifb::{Window, Key};
+use crate::input::{Action, Bindings, InputFrame};
+use crate::maze::{ceiling_height, is_walkable};
+
+const PITCH_SCALE: f32 = 150.0; // Píxeles de horizonte por radián de mouse
+const MAX_PITCH: f32 = 80.0;
+pub const MIN_SENSITIVITY: f32 = 0.0005;
+pub const MAX_SENSITIVITY: f32 = 0.05;
+const PITCH_KEY_SPEED: f32 = 120.0; // Píxeles por segundo con las flechas
+
+// Alturas expresadas como fracción de la altura de pared
+pub const STAND_EYE_HEIGHT: f32 = 0.5;
+const CROUCH_EYE_HEIGHT: f32 = 0.25;
+const HEAD_ROOM: f32 = 0.1; // Espacio entre los ojos y la coronilla
+const CROUCH_SPEED: f32 = 2.0; // Alturas de pared por segundo
+const CROUCH_MOVE_MULTIPLIER: f32 = 0.5;
+const JUMP_SPEED: f32 = 2.0;
+const GRAVITY: f32 = 13.0;
+
+// Ciclo de caminata: la fase avanza un entero por cada paso
+const STEPS_PER_SECOND: f32 = 2.0;
+const BOB_RAMP_SPEED: f32 = 4.0; // Qué tan rápido aparece o se apaga el balanceo
 
 pub struct Player {
     pub pos: Vec2,
@@ -13,9 +28,24 @@ pub struct Player {
     pub fov: f32, // campo de vista
     pub move_speed: f32,
     pub run_multiplier: f32,
+    pub radius: f32, // radio de colisión
     pub mouse_control: bool,
-    pub sound_sink: Option<Arc<Mutex<Sink>>>, // Control de sonido
-    pub _stream: Option<Arc<OutputStream>>,  // Mantenemos el OutputStream vivo
+    pub mouse_sensitivity: f32, // radianes por píxel de movimiento del mouse
+    pub vertical_look: bool,
+    pub pitch: f32, // desplazamiento vertical del horizonte en píxeles
+    pub eye_height: f32, // altura de los ojos (0 = piso, 1 = techo)
+    pub crouching: bool,
+    base_eye_height: f32, // altura de los ojos sin contar el salto
+    jump_height: f32,
+    vertical_speed: f32,
+    last_mouse_pos: Option<(f32, f32)>,
+    mouse_yaw: f32,  // Giro del mouse leído en el cuadro y aún no aplicado
+    edge_turn: f32,  // -1, 0 o 1: cursor detenido contra un borde
+    pub walk_phase: f32, // pasos dados; cada entero es un pie tocando el piso
+    pub bob_amount: f32, // 0 = quieto, 1 = caminando
+    footstep: bool, // se dio un paso durante la última actualización
+    blocked: bool, // una pared detuvo el último movimiento
+    bump: bool, // se chocó con una pared durante la última actualización
 }
 
 impl Player {
@@ -25,100 +55,329 @@ impl Player {
             pos2d,
             a,
             fov,
-            move_speed: 3.0, // valor por defecto
+            move_speed: 150.0, // unidades por segundo
             run_multiplier: 2.0, // valor por defecto para la velocidad al correr
+            radius: 6.0,
             mouse_control: false, // Inicializa como None
-            sound_sink: None, // Inicialmente no hay sonido
-            _stream: None, // Inicialmente no hay OutputStream
+            mouse_sensitivity: 0.005,
+            vertical_look: false,
+            pitch: 0.0,
+            eye_height: STAND_EYE_HEIGHT,
+            crouching: false,
+            base_eye_height: STAND_EYE_HEIGHT,
+            jump_height: 0.0,
+            vertical_speed: 0.0,
+            last_mouse_pos: None,
+            mouse_yaw: 0.0,
+            edge_turn: 0.0,
+            walk_phase: 0.0,
+            bob_amount: 0.0,
+            footstep: false,
+            blocked: false,
+            bump: false,
         }
     }
 
-    pub fn process_events(&mut self, window: &Window, maze: &Vec<Vec<char>>, block_size: usize) {
-        const ROTATION_SPEED: f32 = std::f32::consts::PI / 50.0; // Velocidad de rotación
-    
-        // Solo procesar la rotación con el mouse si mouse_control es verdadero
-        if self.mouse_control {
-            if let Some((mouse_x, _mouse_y)) = window.get_mouse_pos(minifb::MouseMode::Pass) {
-                let window_width = 1080.0;
-                let center_zone_left = window_width * 0.4;
-                let center_zone_right = window_width * 0.6;
-                let rotation_speed = 0.05;
+    pub fn process_events(
+        &mut self,
+        input: &InputFrame,
+        bindings: &Bindings,
+        maze: &[Vec<char>],
+        block_size: usize,
+        dt: f32,
+    ) {
+        const ROTATION_SPEED: f32 = std::f32::consts::PI; // Radianes por segundo
+        const EDGE_TURN_SPEED: f32 = 2.5; // Radianes por segundo
+
+        // El giro del mouse se lee una vez por cuadro y se aplica en el
+        // primer paso; el giro contra el borde depende del tiempo y se
+        // reparte entre todos los pasos
+        self.a += std::mem::take(&mut self.mouse_yaw) + self.edge_turn * EDGE_TURN_SPEED * dt;
     
-                if mouse_x < center_zone_left {
-                    self.a -= rotation_speed;
-                } else if mouse_x > center_zone_right {
-                    self.a += rotation_speed;
-                }
-            }
+        // Teclas de giro para rotación adicional
+        if bindings.is_down(input, Action::TurnLeft) {
+            self.a -= ROTATION_SPEED * dt;
         }
     
-        // Manejo de teclas A y D para rotación adicional
-        if window.is_key_down(Key::A) {
-            self.a -= ROTATION_SPEED;
+        if bindings.is_down(input, Action::TurnRight) {
+            self.a += ROTATION_SPEED * dt;
         }
     
-        if window.is_key_down(Key::D) {
-            self.a += ROTATION_SPEED;
+        // Inclinación vertical con las flechas
+        if bindings.is_down(input, Action::LookUp) {
+            self.pitch = (self.pitch + PITCH_KEY_SPEED * dt).min(MAX_PITCH);
         }
-    
-        let speed = if window.is_key_down(Key::LeftShift) {
+
+        if bindings.is_down(input, Action::LookDown) {
+            self.pitch = (self.pitch - PITCH_KEY_SPEED * dt).max(-MAX_PITCH);
+        }
+
+        self.update_vertical(input, bindings, maze, block_size, dt);
+
+        let mut speed = if bindings.is_down(input, Action::Run) {
             self.move_speed * self.run_multiplier
         } else {
             self.move_speed
-        };
+        } * dt;
+
+        if self.crouching {
+            speed *= CROUCH_MOVE_MULTIPLIER;
+        }
     
+        // Dirección de avance y dirección lateral (derecha) según el ángulo de vista
+        let forward = Vec2::new(self.a.cos(), self.a.sin());
+        let right = Vec2::new(-self.a.sin(), self.a.cos());
+
+        let mut direction = Vec2::new(0.0, 0.0);
+        if bindings.is_down(input, Action::Forward) {
+            direction += forward;
+        }
+        if bindings.is_down(input, Action::Back) {
+            direction -= forward;
+        }
+        if bindings.is_down(input, Action::StrafeRight) {
+            direction += right;
+        }
+        if bindings.is_down(input, Action::StrafeLeft) {
+            direction -= right;
+        }
+
+        // Solo se cuenta el choque al llegar a la pared, no mientras se
+        // sigue empujando o deslizando contra ella
+        let was_blocked = self.blocked;
+        self.blocked = false;
         let mut moved = false;
-    
-        // Calcular la nueva posición en función de la dirección actual y la velocidad
-        let new_x = self.pos.x + self.a.cos() * speed;
-        let new_y = self.pos.y + self.a.sin() * speed;
-        let new_i = (new_x / block_size as f32) as usize;
-        let new_j = (new_y / block_size as f32) as usize;
-    
-        if window.is_key_down(Key::W) {
-            if maze[new_j][new_i] != ' ' && maze[new_j][new_i] != 's' && maze[new_j][new_i] != 'g' {
-                return;
-            }
-            self.pos.x = new_x;
-            self.pos.y = new_y;
-            moved = true;
+        if direction.norm() > 0.0 {
+            moved = self.try_move(direction.normalize() * speed, maze, block_size);
         }
+        self.bump = self.blocked && !was_blocked;
     
-        if window.is_key_down(Key::S) {
-            let new_x = self.pos.x - self.a.cos() * speed;
-            let new_y = self.pos.y - self.a.sin() * speed;
-            let new_i = (new_x / block_size as f32) as usize;
-            let new_j = (new_y / block_size as f32) as usize;
-            if maze[new_j][new_i] != ' ' && maze[new_j][new_i] != 's' && maze[new_j][new_i] != 'g' {
-                return;
+        let mut pace = if bindings.is_down(input, Action::Run) {
+            self.run_multiplier
+        } else {
+            1.0
+        };
+
+        if self.crouching {
+            pace *= CROUCH_MOVE_MULTIPLIER;
+        }
+
+        self.update_walk_cycle(moved, pace, dt);
+    }
+
+    // Avanza la fase de caminata; correr acelera el ciclo y cada vez que la
+    // fase cruza un entero se marca un paso
+    fn update_walk_cycle(&mut self, moved: bool, pace: f32, dt: f32) {
+        self.footstep = false;
+
+        let on_ground = self.jump_height <= 0.0;
+        if moved && on_ground {
+            let previous = self.walk_phase;
+            self.walk_phase += STEPS_PER_SECOND * pace * dt;
+            self.footstep = self.walk_phase.floor() > previous.floor();
+            self.bob_amount = (self.bob_amount + BOB_RAMP_SPEED * dt).min(1.0);
+        } else {
+            self.bob_amount = (self.bob_amount - BOB_RAMP_SPEED * dt).max(0.0);
+        }
+    }
+
+    // Devuelve true una sola vez por cada paso dado
+    pub fn take_footstep(&mut self) -> bool {
+        std::mem::take(&mut self.footstep)
+    }
+
+    // Devuelve true una sola vez por cada choque con una pared
+    pub fn take_bump(&mut self) -> bool {
+        std::mem::take(&mut self.bump)
+    }
+
+    // Sube y baja (-1 a 1) una vez por paso; el punto más bajo coincide con
+    // cada pisada
+    pub fn walk_bob(&self) -> f32 {
+        -(self.walk_phase * std::f32::consts::TAU).cos() * self.bob_amount
+    }
+
+    // Vaivén lateral (-1 a 1), un ciclo completo cada dos pasos
+    pub fn walk_sway(&self) -> f32 {
+        (self.walk_phase * std::f32::consts::PI).sin() * self.bob_amount
+    }
+
+    // Vista relativa: el giro es proporcional a cuánto se movió el cursor
+    // desde el cuadro anterior. Se llama una vez por cuadro; process_events
+    // aplica el giro acumulado
+    pub fn process_mouse(&mut self, input: &InputFrame) {
+        const EDGE_BAND: f32 = 0.02; // Fracción del ancho de la ventana
+
+        self.edge_turn = 0.0;
+        if !self.mouse_control {
+            self.last_mouse_pos = None;
+            self.mouse_yaw = 0.0;
+            return;
+        }
+
+        if let Some((mouse_x, mouse_y)) = input.mouse {
+            let mut dx = 0.0;
+
+            if let Some((last_x, last_y)) = self.last_mouse_pos {
+                dx = mouse_x - last_x;
+                let dy = mouse_y - last_y;
+
+                self.mouse_yaw += dx * self.mouse_sensitivity;
+
+                if self.vertical_look {
+                    self.pitch = (self.pitch - dy * self.mouse_sensitivity * PITCH_SCALE)
+                        .clamp(-MAX_PITCH, MAX_PITCH);
+                }
             }
+
+            // minifb no permite recentrar ni capturar el cursor, así que si
+            // queda detenido contra un borde seguimos girando hacia ese lado
+            if dx == 0.0 {
+                let width = input.width;
+                let edge = width as f32 * EDGE_BAND;
+
+                if mouse_x <= edge {
+                    self.edge_turn = -1.0;
+                } else if mouse_x >= width as f32 - edge {
+                    self.edge_turn = 1.0;
+                }
+            }
+
+            self.last_mouse_pos = Some((mouse_x, mouse_y));
+        }
+    }
+
+    pub fn adjust_mouse_sensitivity(&mut self, factor: f32) {
+        self.mouse_sensitivity =
+            (self.mouse_sensitivity * factor).clamp(MIN_SENSITIVITY, MAX_SENSITIVITY);
+    }
+
+    pub fn toggle_vertical_look(&mut self) {
+        self.vertical_look = !self.vertical_look;
+        if !self.vertical_look {
+            self.pitch = 0.0;
+        }
+    }
+
+    // Salto y agachado: cambian la altura de los ojos respetando el techo
+    fn update_vertical(
+        &mut self,
+        input: &InputFrame,
+        bindings: &Bindings,
+        maze: &[Vec<char>],
+        block_size: usize,
+        dt: f32,
+    ) {
+        let ceiling = self
+            .min_ceiling_at(self.pos.x, self.pos.y, maze, block_size)
+            .unwrap_or(1.0);
+
+        // Bajo un techo bajo no es posible ponerse de pie
+        self.crouching =
+            bindings.is_down(input, Action::Crouch) || ceiling < STAND_EYE_HEIGHT + HEAD_ROOM;
+
+        let target = if self.crouching {
+            CROUCH_EYE_HEIGHT
+        } else {
+            STAND_EYE_HEIGHT
+        };
+        let max_step = CROUCH_SPEED * dt;
+        self.base_eye_height += (target - self.base_eye_height).clamp(-max_step, max_step);
+
+        let on_ground = self.jump_height <= 0.0;
+        if on_ground && !self.crouching && bindings.is_down(input, Action::Jump) {
+            self.vertical_speed = JUMP_SPEED;
+        }
+
+        self.vertical_speed -= GRAVITY * dt;
+        self.jump_height += self.vertical_speed * dt;
+
+        // La cabeza no puede atravesar el techo
+        let max_jump = (ceiling - HEAD_ROOM - self.base_eye_height).max(0.0);
+        if self.jump_height > max_jump {
+            self.jump_height = max_jump;
+            self.vertical_speed = self.vertical_speed.min(0.0);
+        }
+
+        if self.jump_height <= 0.0 {
+            self.jump_height = 0.0;
+            self.vertical_speed = 0.0;
+        }
+
+        self.eye_height = self.base_eye_height + self.jump_height;
+    }
+
+    // Mueve cada eje por separado para que al chocar en ángulo contra una
+    // pared el jugador se deslice a lo largo de ella en lugar de detenerse
+    fn try_move(&mut self, step: Vec2, maze: &[Vec<char>], block_size: usize) -> bool {
+        let start = self.pos;
+
+        let new_x = self.pos.x + step.x;
+        if self.can_stand_at(new_x, self.pos.y, maze, block_size) {
             self.pos.x = new_x;
+        } else if step.x != 0.0 {
+            self.blocked = true;
+        }
+
+        let new_y = self.pos.y + step.y;
+        if self.can_stand_at(self.pos.x, new_y, maze, block_size) {
             self.pos.y = new_y;
-            moved = true;
+        } else if step.y != 0.0 {
+            self.blocked = true;
         }
-    
-        if moved {
-            // Ajustar la velocidad del sonido según si Shift está presionado
-            let speed_multiplier = if window.is_key_down(Key::LeftShift) { 2.0 } else { 1.0 };
-            
-            // Si el jugador se mueve y no hay sonido en reproducción, inicia el sonido
-            if self.sound_sink.is_none() {
-                self.start_walking_sound(speed_multiplier);
-            } else {
-                // Ajustar la velocidad del sonido si ya está en reproducción
-                if let Some(sink) = &self.sound_sink {
-                    sink.lock().unwrap().set_speed(speed_multiplier);
+
+        self.pos != start
+    }
+
+    // Revisa si un círculo de radio `self.radius` centrado en (x, y) cabe
+    // sin tocar ninguna pared y con la cabeza por debajo del techo
+    fn can_stand_at(&self, x: f32, y: f32, maze: &[Vec<char>], block_size: usize) -> bool {
+        self.min_ceiling_at(x, y, maze, block_size)
+            .is_some_and(|ceiling| ceiling >= self.eye_height + HEAD_ROOM)
+    }
+
+    // Techo más bajo entre las celdas que toca el círculo del jugador, o
+    // None si alguna de ellas es pared
+    fn min_ceiling_at(&self, x: f32, y: f32, maze: &[Vec<char>], block_size: usize) -> Option<f32> {
+        let bs = block_size as f32;
+        let r = self.radius;
+
+        let min_i = ((x - r) / bs).floor() as isize;
+        let max_i = ((x + r) / bs).floor() as isize;
+        let min_j = ((y - r) / bs).floor() as isize;
+        let max_j = ((y + r) / bs).floor() as isize;
+
+        let mut ceiling = 1.0_f32;
+
+        for j in min_j..=max_j {
+            for i in min_i..=max_i {
+                // Punto de la celda más cercano al centro del círculo
+                let closest_x = x.clamp(i as f32 * bs, (i + 1) as f32 * bs);
+                let closest_y = y.clamp(j as f32 * bs, (j + 1) as f32 * bs);
+                let dx = x - closest_x;
+                let dy = y - closest_y;
+
+                if dx * dx + dy * dy >= r * r {
+                    continue;
                 }
-            }
-        } else {
-            // Si el jugador deja de moverse y el sonido está en reproducción, detén el sonido
-            if let Some(sink) = &self.sound_sink {
-                if !sink.lock().unwrap().empty() {
-                    sink.lock().unwrap().stop();
-                    self.sound_sink = None;
+
+                // Fuera del laberinto se considera pared
+                let cell = if i < 0 || j < 0 {
+                    None
+                } else {
+                    maze.get(j as usize).and_then(|row| row.get(i as usize))
+                };
+
+                match cell {
+                    Some(&cell) if is_walkable(cell) => {
+                        ceiling = ceiling.min(ceiling_height(cell));
+                    }
+                    _ => return None,
                 }
             }
         }
+
+        Some(ceiling)
     }
 
     pub fn update2d_position(&mut self, block_size: usize, block_siz2d: usize){
@@ -131,32 +390,7 @@ impl Player {
     }
     
 
-    pub fn start_walking_sound(&mut self, speed_multiplier: f32) {
-        let (stream, stream_handle) = OutputStream::try_default().unwrap();
-        let sink = Sink::try_new(&stream_handle).unwrap();
-    
-        let file = File::open("./src/sound/steps.mp3").unwrap();
-        let source = Decoder::new(BufReader::new(file)).unwrap().repeat_infinite();
-    
-        sink.append(source);
-        
-        // Ajustar la frecuencia del sonido
-        sink.set_speed(speed_multiplier);
-    
-        // Mantenemos el OutputStream vivo usando Arc
-        self._stream = Some(Arc::new(stream));
-        self.sound_sink = Some(Arc::new(Mutex::new(sink)));
-    }
-
-    pub fn stop_walking_sound(&mut self) {
-        if let Some(sink) = self.sound_sink.take() {
-            if !sink.lock().unwrap().empty() {
-                sink.lock().unwrap().stop();
-            }
-        }
-    }
-
-    pub fn get_current_tile(&self, maze: &Vec<Vec<char>>, block_size: usize) -> Option<char> {
+    pub fn get_current_tile(&self, maze: &[Vec<char>], block_size: usize) -> Option<char> {
         let i = (self.pos.x / block_size as f32) as usize;
         let j = (self.pos.y / block_size as f32) as usize;
 
@@ -0,0 +1,53 @@
+use crate::input::InputFrame;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Lines, Write};
+use std::path::Path;
+
+// Primera línea de toda grabación; si cambia el formato cambia la versión
+const HEADER: &str = "# maze_runner input v1";
+
+// Escribe la entrada de cada cuadro, una línea por cuadro
+pub struct Recorder {
+    out: BufWriter<File>,
+}
+
+impl Recorder {
+    pub fn create(path: &Path) -> io::Result<Self> {
+        let mut out = BufWriter::new(File::create(path)?);
+        writeln!(out, "{}", HEADER)?;
+        Ok(Recorder { out })
+    }
+
+    pub fn write(&mut self, frame: &InputFrame) -> io::Result<()> {
+        writeln!(self.out, "{}", frame.to_line())
+    }
+}
+
+// Lee una grabación cuadro por cuadro
+pub struct Replay {
+    lines: Lines<BufReader<File>>,
+    line: usize,
+}
+
+impl Replay {
+    pub fn open(path: &Path) -> Result<Self, String> {
+        let file = File::open(path).map_err(|e| e.to_string())?;
+        let mut lines = BufReader::new(file).lines();
+        match lines.next() {
+            Some(Ok(header)) if header == HEADER => Ok(Replay { lines, line: 1 }),
+            Some(Err(e)) => Err(e.to_string()),
+            _ => Err("not an input recording".into()),
+        }
+    }
+
+    // None cuando se termina la grabación
+    pub fn next_frame(&mut self) -> Option<Result<InputFrame, String>> {
+        let line = self.lines.next()?;
+        self.line += 1;
+        Some(
+            line.map_err(|e| e.to_string())
+                .and_then(|line| InputFrame::from_line(&line))
+                .map_err(|e| format!("line {}: {}", self.line, e)),
+        )
+    }
+}
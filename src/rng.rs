@@ -0,0 +1,42 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// Generador xorshift64: suficiente para decisiones del juego, no para nada
+// criptográfico
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        // El estado nunca puede ser cero o el generador se queda en cero
+        Rng {
+            state: seed.max(1),
+        }
+    }
+
+    // Semilla tomada del reloj, distinta en cada ejecución
+    pub fn from_time() -> Self {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_nanos() as u64)
+            .unwrap_or(0);
+        Rng::new(nanos)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        let mut x = self.state;
+        x ^= x << 13;
+        x ^= x >> 7;
+        x ^= x << 17;
+        self.state = x;
+        x
+    }
+
+    // Entero uniforme en 0..n
+    pub fn below(&mut self, n: usize) -> usize {
+        if n == 0 {
+            return 0;
+        }
+        (self.next_u64() % n as u64) as usize
+    }
+}
@@ -0,0 +1,154 @@
+use crate::game::Level;
+use crate::leaderboard::Run;
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+// Sube cada vez que cambia lo que se guarda; las partidas de otra versión
+// no se cargan
+pub const VERSION: u32 = 2;
+
+const HEADER: &str = "# maze_runner saved game";
+
+// Todo lo necesario para continuar una partida a mitad del laberinto
+pub struct SaveState {
+    pub level: Level,
+    pub maze_key: String, // Para detectar si el archivo del laberinto cambió
+    pub position: (f32, f32),
+    pub angle: f32,
+    pub pitch: f32,
+    pub run: Run,
+    pub lantern: (f32, f32), // Posición y dirección del balanceo del farol
+    pub explored: Vec<(usize, usize)>, // Celdas (columna, fila) que ya se vieron
+}
+
+// El archivo va junto al de configuración
+pub fn path_for(config_path: &Path) -> PathBuf {
+    config_path.with_file_name("save.txt")
+}
+
+fn level_to_text(level: &Level) -> String {
+    match level {
+        Level::Builtin(n) => format!("builtin {}", n),
+        Level::File(path) => format!("file {}", path.display()),
+        Level::Generated(seed) => format!("seed {}", seed),
+    }
+}
+
+fn level_from_text(text: &str) -> Result<Level, String> {
+    match text.split_once(' ') {
+        Some(("builtin", n)) => match n.parse() {
+            Ok(n) if (1..=3).contains(&n) => Ok(Level::Builtin(n)),
+            _ => Err(format!("unknown maze {}", n)),
+        },
+        Some(("file", path)) => Ok(Level::File(PathBuf::from(path))),
+        Some(("seed", seed)) => seed
+            .parse()
+            .map(Level::Generated)
+            .map_err(|_| format!("invalid seed {}", seed)),
+        _ => Err(format!("invalid level {}", text)),
+    }
+}
+
+fn parse_numbers<const N: usize>(key: &str, text: &str) -> Result<[f32; N], String> {
+    let numbers: Vec<f32> = text
+        .split_whitespace()
+        .map(|n| n.parse::<f32>().ok().filter(|n| n.is_finite()))
+        .collect::<Option<_>>()
+        .ok_or_else(|| format!("{}: invalid number in {}", key, text))?;
+    numbers
+        .try_into()
+        .map_err(|_| format!("{}: expected {} numbers", key, N))
+}
+
+fn parse_cell(text: &str) -> Option<(usize, usize)> {
+    let (col, row) = text.split_once(',')?;
+    Some((col.parse().ok()?, row.parse().ok()?))
+}
+
+impl SaveState {
+    pub fn to_text(&self) -> String {
+        let explored: Vec<String> = self
+            .explored
+            .iter()
+            .map(|(col, row)| format!("{},{}", col, row))
+            .collect();
+        format!(
+            "{}\nversion = {}\nlevel = {}\nmaze = {}\nposition = {} {}\nangle = {}\npitch = {}\n\
+             time = {}\nsteps = {}\nlantern = {} {}\nexplored = {}\n",
+            HEADER,
+            VERSION,
+            level_to_text(&self.level),
+            self.maze_key,
+            self.position.0,
+            self.position.1,
+            self.angle,
+            self.pitch,
+            self.run.time,
+            self.run.steps,
+            self.lantern.0,
+            self.lantern.1,
+            explored.join(" ")
+        )
+    }
+
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut values = HashMap::new();
+        for line in text.lines().map(str::trim) {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (key, value) = line
+                .split_once('=')
+                .ok_or_else(|| format!("expected key = value, found {}", line))?;
+            values.insert(key.trim(), value.trim());
+        }
+        let get = |key: &str| values.get(key).copied().ok_or_else(|| format!("missing {}", key));
+
+        let version = get("version")?;
+        if version.parse::<u32>() != Ok(VERSION) {
+            return Err(format!("save version {} is not supported (expected {})", version, VERSION));
+        }
+
+        let [x, y] = parse_numbers("position", get("position")?)?;
+        let [angle] = parse_numbers("angle", get("angle")?)?;
+        let [pitch] = parse_numbers("pitch", get("pitch")?)?;
+        let [time] = parse_numbers("time", get("time")?)?;
+        let [lantern_x, lantern_direction] = parse_numbers("lantern", get("lantern")?)?;
+        let steps = get("steps")?;
+        let explored = get("explored")?
+            .split_whitespace()
+            .map(|cell| parse_cell(cell).ok_or_else(|| format!("explored: invalid cell {}", cell)))
+            .collect::<Result<_, _>>()?;
+
+        Ok(SaveState {
+            level: level_from_text(get("level")?)?,
+            maze_key: get("maze")?.to_string(),
+            position: (x, y),
+            angle,
+            pitch,
+            run: Run {
+                time: time.max(0.0),
+                steps: steps.parse().map_err(|_| format!("steps: invalid number {}", steps))?,
+            },
+            lantern: (lantern_x, lantern_direction.signum()),
+            explored,
+        })
+    }
+
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let text = fs::read_to_string(path).map_err(|e| e.to_string())?;
+        SaveState::parse(&text)
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        // Se escribe aparte y se renombra para no dejar una partida a medias
+        let temp = path.with_extension("tmp");
+        fs::write(&temp, self.to_text())?;
+        fs::rename(temp, path)
+    }
+}
@@ -0,0 +1,62 @@
+use super::menu::draw_centered;
+use super::{Scene, SceneEvent};
+use crate::font::Align;
+use crate::game::{Game, END_MUSIC};
+use crate::leaderboard::{format_time, MAX_ENTRIES};
+use crate::ttf::TtfStyle;
+use minifb::{Key, KeyRepeat};
+
+const TEXT_COLOR: u32 = 0xffffff;
+const BEST_COLOR: u32 = 0xffd75e;
+const PANEL_WIDTH: usize = 200;
+
+// Pantalla final al llegar a la salida, con el tiempo de la partida y los
+// mejores tiempos del laberinto
+pub struct EndScene;
+
+impl Scene for EndScene {
+    fn enter(&mut self, game: &mut Game) {
+        game.music.play(&mut game.audio, END_MUSIC, false);
+        game.set_cursor_visible(true);
+    }
+
+    fn handle_input(&mut self, game: &mut Game) -> Option<SceneEvent> {
+        if game.input.is_pressed(Key::Enter, KeyRepeat::No) {
+            game.play_ui_sound("win4.mp3");
+            return Some(SceneEvent::Continue);
+        }
+        if game.input.is_pressed(Key::Escape, KeyRepeat::No) {
+            return Some(SceneEvent::Quit);
+        }
+        None
+    }
+
+    fn render(&mut self, game: &mut Game) {
+        game.draw_screen("./src/img/endScreen.png");
+
+        // Panel en el cielo, encima del letrero
+        let best = game.leaderboard.best(&game.maze_key);
+        let fb = &mut game.framebuffer;
+        let panel_x = fb.width.saturating_sub(PANEL_WIDTH) / 2;
+        fb.shade_rect(panel_x, 4, PANEL_WIDTH, 34 + 9 * MAX_ENTRIES, 0x000000, 0.6);
+
+        let summary = format!("TIME {}  STEPS {}", format_time(game.run.time), game.run.steps);
+        draw_centered(fb, 8, &summary, TEXT_COLOR);
+
+        let title = if game.last_rank == Some(0) { "New best time!" } else { "Best times" };
+        let style = TtfStyle {
+            size: 14.0,
+            color: BEST_COLOR,
+            align: Align::Center,
+            shadow: Some(0x000000),
+        };
+        let x = fb.width / 2;
+        game.ttf.draw(fb, x, 17, title, &style);
+
+        for (i, run) in best.iter().enumerate() {
+            let color = if game.last_rank == Some(i) { BEST_COLOR } else { TEXT_COLOR };
+            let row = format!("{}. {}  {:>4} STEPS", i + 1, format_time(run.time), run.steps);
+            draw_centered(fb, 35 + 9 * i, &row, color);
+        }
+    }
+}
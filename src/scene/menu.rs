@@ -0,0 +1,100 @@
+use crate::font::{self, Align, TextStyle};
+use crate::framebuffer::Framebuffer;
+use crate::game::Game;
+use minifb::{Key, KeyRepeat};
+
+const ROW_HEIGHT: usize = 8;
+const TEXT_COLOR: u32 = 0xb8b0a0;
+const SELECTED_COLOR: u32 = 0xffd75e;
+
+// Lo que el jugador hizo con el menú en este cuadro
+pub enum MenuInput {
+    None,
+    Select,
+    Left,
+    Right,
+}
+
+// Lista vertical navegable con las flechas; Enter elige y las flechas
+// laterales cambian valores
+pub struct Menu {
+    pub selected: usize,
+    len: usize,
+}
+
+impl Menu {
+    pub fn new(len: usize) -> Self {
+        Menu { selected: 0, len }
+    }
+
+    pub fn navigate(&mut self, game: &Game) -> MenuInput {
+        let input = &game.input;
+        if input.is_pressed(Key::Up, KeyRepeat::Yes) {
+            self.selected = (self.selected + self.len - 1) % self.len;
+        }
+        if input.is_pressed(Key::Down, KeyRepeat::Yes) {
+            self.selected = (self.selected + 1) % self.len;
+        }
+
+        if input.is_pressed(Key::Enter, KeyRepeat::No) {
+            MenuInput::Select
+        } else if input.is_pressed(Key::Left, KeyRepeat::Yes) {
+            MenuInput::Left
+        } else if input.is_pressed(Key::Right, KeyRepeat::Yes) {
+            MenuInput::Right
+        } else {
+            MenuInput::None
+        }
+    }
+
+    // Cada fila es una etiqueta y, si tiene, su valor alineado a la derecha
+    // dentro de `width`, centrado en el cuadro. Si no caben todas las filas
+    // se desplaza para que la elegida siempre se vea
+    pub fn draw(&self, framebuffer: &mut Framebuffer, width: usize, y: usize, rows: &[(String, String)]) {
+        let x = framebuffer.width.saturating_sub(width) / 2;
+        let visible = (framebuffer.height.saturating_sub(y) / ROW_HEIGHT).max(1);
+        let first = (self.selected + 1).saturating_sub(visible);
+
+        for (i, (label, value)) in rows.iter().enumerate().skip(first).take(visible) {
+            let selected = i == self.selected;
+            framebuffer.set_current_color(if selected { SELECTED_COLOR } else { TEXT_COLOR });
+
+            let row_y = y + (i - first) * ROW_HEIGHT;
+            if selected {
+                framebuffer.draw_text(x.saturating_sub(8), row_y, ">");
+            }
+            framebuffer.draw_text(x, row_y, label);
+            // En una ventana angosta el valor se corre a la izquierda en vez
+            // de salirse del cuadro
+            let value_x = (x + width).saturating_sub(font::text_width(value, 1));
+            framebuffer.draw_text(value_x, row_y, value);
+        }
+    }
+}
+
+// Mensaje breve de Game::show_notice, arriba al centro
+pub fn draw_notice(game: &mut Game) {
+    if let Some(text) = game.notice().map(str::to_string) {
+        // Los mensajes largos (errores al guardar, por ejemplo) se parten
+        let style = TextStyle {
+            align: Align::Center,
+            wrap: Some(game.framebuffer.width.saturating_sub(40)),
+            shadow: Some(0x000000),
+            ..TextStyle::default()
+        };
+        let x = game.framebuffer.width / 2;
+        game.framebuffer.draw_text_styled(x, 24, &text, &style);
+    }
+}
+
+// Texto centrado horizontalmente en el cuadro, con sombra para que se lea
+// sobre las imágenes de fondo
+pub fn draw_centered(framebuffer: &mut Framebuffer, y: usize, text: &str, color: u32) {
+    let style = TextStyle {
+        color,
+        align: Align::Center,
+        shadow: Some(0x000000),
+        ..TextStyle::default()
+    };
+    framebuffer.draw_text_styled(framebuffer.width / 2, y, text, &style);
+}
@@ -0,0 +1,276 @@
+mod end;
+mod menu;
+mod pause;
+mod playing;
+mod settings;
+mod title;
+
+use crate::game::Game;
+
+// Identificador de cada escena; la tabla de transiciones trabaja con estos
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SceneId {
+    Title,
+    Playing,
+    Pause,
+    Settings,
+    End,
+}
+
+// Lo que una escena avisa que pasó; la tabla decide qué hacer con ello
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SceneEvent {
+    Start,    // Se eligió un laberinto
+    Pause,
+    Resume,
+    Restart,  // Reiniciar el laberinto actual
+    Settings,
+    Back,     // Cerrar la pantalla de ajustes
+    ToTitle,
+    Goal,     // Se llegó a la salida
+    Continue, // Volver al título desde la pantalla final
+    Quit,
+}
+
+#[derive(Clone, Copy, Debug)]
+enum Action {
+    Push(SceneId),
+    Pop,
+    Replace(SceneId), // Quita la escena de arriba y pone otra
+    Reset(SceneId),   // Vacía la pila y deja solo esta escena
+    Quit,
+}
+
+const FADE: f32 = 0.35; // Segundos de cada mitad del fundido
+
+// Escena actual, evento, acción, segundos hasta negro antes de la acción y
+// segundos desde negro después de ella (0 = sin fundido)
+const TRANSITIONS: &[(SceneId, SceneEvent, Action, f32, f32)] = &[
+    (SceneId::Title, SceneEvent::Start, Action::Replace(SceneId::Playing), FADE, FADE),
+    (SceneId::Title, SceneEvent::Settings, Action::Push(SceneId::Settings), 0.0, 0.0),
+    (SceneId::Title, SceneEvent::Quit, Action::Quit, FADE, 0.0),
+    (SceneId::Playing, SceneEvent::Pause, Action::Push(SceneId::Pause), 0.0, 0.0),
+    (SceneId::Playing, SceneEvent::Goal, Action::Replace(SceneId::End), FADE, FADE),
+    (SceneId::Pause, SceneEvent::Resume, Action::Pop, 0.0, 0.0),
+    (SceneId::Pause, SceneEvent::Restart, Action::Reset(SceneId::Playing), FADE, FADE),
+    (SceneId::Pause, SceneEvent::Settings, Action::Push(SceneId::Settings), 0.0, 0.0),
+    (SceneId::Pause, SceneEvent::ToTitle, Action::Reset(SceneId::Title), FADE, FADE),
+    (SceneId::Settings, SceneEvent::Back, Action::Pop, 0.0, 0.0),
+    (SceneId::End, SceneEvent::Continue, Action::Replace(SceneId::Title), FADE, FADE),
+    (SceneId::End, SceneEvent::Quit, Action::Quit, FADE, 0.0),
+];
+
+// Una pantalla o capa del juego. Solo la escena de arriba recibe entrada y
+// avanza; las de abajo se siguen dibujando si la de arriba es superpuesta
+pub trait Scene {
+    fn enter(&mut self, _game: &mut Game) {}
+    fn exit(&mut self, _game: &mut Game) {}
+    // Una vez por cuadro, para teclas que deben leerse una sola vez
+    fn handle_input(&mut self, _game: &mut Game) -> Option<SceneEvent> {
+        None
+    }
+    // Una vez por paso fijo de simulación
+    fn update(&mut self, _game: &mut Game, _dt: f32) -> Option<SceneEvent> {
+        None
+    }
+    fn render(&mut self, game: &mut Game);
+    // Las escenas superpuestas dejan ver la escena de abajo
+    fn is_overlay(&self) -> bool {
+        false
+    }
+}
+
+fn create(id: SceneId) -> Box<dyn Scene> {
+    match id {
+        SceneId::Title => Box::new(title::TitleScene),
+        SceneId::Playing => Box::new(playing::PlayingScene),
+        SceneId::Pause => Box::new(pause::PauseScene::new()),
+        SceneId::Settings => Box::new(settings::SettingsScene::new()),
+        SceneId::End => Box::new(end::EndScene),
+    }
+}
+
+// Fundido en curso: oscurece hasta negro, aplica la acción y aclara
+struct Fade {
+    action: Option<Action>, // Se toma al llegar a negro
+    fade_out: f32,
+    fade_in: f32,
+    elapsed: f32,
+}
+
+impl Fade {
+    // Opacidad del negro en este momento
+    fn alpha(&self) -> f32 {
+        if self.elapsed < self.fade_out {
+            self.elapsed / self.fade_out
+        } else if self.fade_in > 0.0 {
+            1.0 - (self.elapsed - self.fade_out) / self.fade_in
+        } else {
+            1.0
+        }
+    }
+}
+
+pub struct SceneStack {
+    scenes: Vec<(SceneId, Box<dyn Scene>)>,
+    fade: Option<Fade>,
+    quit: bool,
+}
+
+impl SceneStack {
+    pub fn new(game: &mut Game, first: SceneId) -> Self {
+        let mut stack = SceneStack {
+            scenes: Vec::new(),
+            fade: None,
+            quit: false,
+        };
+        stack.push(game, first);
+        stack
+    }
+
+    pub fn is_running(&self) -> bool {
+        !self.quit && !self.scenes.is_empty()
+    }
+
+    // Un cuadro completo: entrada, pasos de simulación, fundido y dibujo
+    pub fn frame(&mut self, game: &mut Game) {
+        let steps = game.input.steps;
+        let dt = game.timestep.step;
+        let frame_time = steps as f32 * dt;
+
+        game.handle_audio_shortcuts();
+        game.audio.update();
+        game.music.update(&mut game.audio, frame_time);
+
+        // Durante un fundido las escenas no reciben entrada ni avanzan
+        if self.fade.is_none() {
+            if let Some(event) = self.top().and_then(|scene| scene.handle_input(game)) {
+                self.trigger(game, event);
+            }
+
+            for _ in 0..steps {
+                if self.fade.is_some() || self.quit {
+                    break;
+                }
+                if let Some(event) = self.top().and_then(|scene| scene.update(game, dt)) {
+                    self.trigger(game, event);
+                }
+            }
+        }
+
+        self.advance_fade(game, frame_time);
+        if self.quit {
+            return;
+        }
+
+        self.render(game);
+        game.frame_limiter.wait();
+    }
+
+    fn top(&mut self) -> Option<&mut Box<dyn Scene>> {
+        self.scenes.last_mut().map(|(_, scene)| scene)
+    }
+
+    // Busca en la tabla qué hace el evento en la escena actual; los eventos
+    // sin transición se ignoran
+    fn trigger(&mut self, game: &mut Game, event: SceneEvent) {
+        let Some(current) = self.scenes.last().map(|(id, _)| *id) else {
+            return;
+        };
+        let Some(&(_, _, action, fade_out, fade_in)) = TRANSITIONS
+            .iter()
+            .find(|(from, on, ..)| *from == current && *on == event)
+        else {
+            return;
+        };
+
+        if fade_out > 0.0 || fade_in > 0.0 {
+            self.fade = Some(Fade {
+                action: Some(action),
+                fade_out,
+                fade_in,
+                elapsed: 0.0,
+            });
+            // Un fundido sin salida aplica la acción de inmediato
+            self.advance_fade(game, 0.0);
+        } else {
+            self.apply(game, action);
+        }
+    }
+
+    fn advance_fade(&mut self, game: &mut Game, dt: f32) {
+        let Some(fade) = &mut self.fade else {
+            return;
+        };
+        fade.elapsed += dt;
+
+        if fade.elapsed >= fade.fade_out {
+            if let Some(action) = fade.action.take() {
+                self.apply(game, action);
+            }
+        }
+
+        if let Some(fade) = &self.fade {
+            if fade.elapsed >= fade.fade_out + fade.fade_in {
+                self.fade = None;
+            }
+        }
+    }
+
+    fn apply(&mut self, game: &mut Game, action: Action) {
+        match action {
+            Action::Push(id) => self.push(game, id),
+            Action::Pop => self.pop(game),
+            Action::Replace(id) => {
+                self.pop(game);
+                self.push(game, id);
+            }
+            Action::Reset(id) => {
+                self.clear(game);
+                self.push(game, id);
+            }
+            Action::Quit => {
+                self.clear(game);
+                self.quit = true;
+            }
+        }
+    }
+
+    fn push(&mut self, game: &mut Game, id: SceneId) {
+        let mut scene = create(id);
+        scene.enter(game);
+        self.scenes.push((id, scene));
+    }
+
+    fn pop(&mut self, game: &mut Game) {
+        if let Some((_, mut scene)) = self.scenes.pop() {
+            scene.exit(game);
+        }
+    }
+
+    // Sale de todas las escenas, de arriba hacia abajo
+    fn clear(&mut self, game: &mut Game) {
+        while !self.scenes.is_empty() {
+            self.pop(game);
+        }
+    }
+
+    // Dibuja desde la última escena opaca hacia arriba y muestra el cuadro
+    // una sola vez
+    fn render(&mut self, game: &mut Game) {
+        let first = self
+            .scenes
+            .iter()
+            .rposition(|(_, scene)| !scene.is_overlay())
+            .unwrap_or(0);
+        for (_, scene) in &mut self.scenes[first..] {
+            scene.render(game);
+        }
+
+        if let Some(fade) = &self.fade {
+            game.framebuffer.overlay(0x000000, fade.alpha());
+        }
+
+        game.present();
+    }
+}
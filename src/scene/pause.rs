@@ -0,0 +1,84 @@
+use super::menu::{Menu, MenuInput};
+use super::{Scene, SceneEvent};
+use crate::font::Align;
+use crate::game::Game;
+use crate::input::Action;
+use crate::ttf::TtfStyle;
+use minifb::{Key, KeyRepeat};
+
+const DIM: f32 = 0.6; // Qué tanto se oscurece el juego detrás
+const MENU_WIDTH: usize = 90;
+
+const ITEMS: [(&str, SceneEvent); 4] = [
+    ("RESUME", SceneEvent::Resume),
+    ("RESTART LEVEL", SceneEvent::Restart),
+    ("SETTINGS", SceneEvent::Settings),
+    ("QUIT TO TITLE", SceneEvent::ToTitle),
+];
+
+// Menú sobre el juego: congela la simulación y el audio
+pub struct PauseScene {
+    menu: Menu,
+}
+
+impl PauseScene {
+    pub fn new() -> Self {
+        PauseScene {
+            menu: Menu::new(ITEMS.len()),
+        }
+    }
+}
+
+impl Scene for PauseScene {
+    fn enter(&mut self, game: &mut Game) {
+        game.audio.pause();
+        game.set_cursor_visible(true);
+    }
+
+    fn exit(&mut self, game: &mut Game) {
+        game.audio.resume();
+        game.set_cursor_visible(!game.player.mouse_control);
+        // El tiempo en pausa no cuenta para la simulación
+        game.timestep.reset();
+    }
+
+    fn handle_input(&mut self, game: &mut Game) -> Option<SceneEvent> {
+        if game.input.is_pressed(Key::Escape, KeyRepeat::No)
+            || game.settings.bindings.is_pressed(&game.input, Action::Pause)
+        {
+            return Some(SceneEvent::Resume);
+        }
+
+        match self.menu.navigate(game) {
+            MenuInput::Select => {
+                let (_, event) = ITEMS[self.menu.selected];
+                game.play_ui_sound("mapSelect.mp3");
+                Some(event)
+            }
+            _ => None,
+        }
+    }
+
+    fn render(&mut self, game: &mut Game) {
+        game.framebuffer.overlay(0x000000, DIM);
+        let title = TtfStyle {
+            size: 24.0,
+            align: Align::Center,
+            shadow: Some(0x000000),
+            ..TtfStyle::default()
+        };
+        // Título y menú alrededor del centro del cuadro
+        let (x, middle) = (game.framebuffer.width / 2, game.framebuffer.height / 2);
+        game.ttf.draw(&mut game.framebuffer, x, middle.saturating_sub(55), "Paused", &title);
+
+        let rows: Vec<(String, String)> = ITEMS
+            .iter()
+            .map(|(label, _)| (label.to_string(), String::new()))
+            .collect();
+        self.menu.draw(&mut game.framebuffer, MENU_WIDTH, middle.saturating_sub(20), &rows);
+    }
+
+    fn is_overlay(&self) -> bool {
+        true
+    }
+}
@@ -0,0 +1,66 @@
+use super::menu::draw_notice;
+use super::{Scene, SceneEvent};
+use crate::game::Game;
+use crate::input::Action;
+use minifb::{Key, KeyRepeat};
+
+// El laberinto elegido en el título o en la línea de comandos (Game::level)
+pub struct PlayingScene;
+
+impl Scene for PlayingScene {
+    fn enter(&mut self, game: &mut Game) {
+        match game.pending_save.take() {
+            Some(state) => game.restore(state),
+            None => game.start_level(game.level.clone()),
+        }
+        game.set_cursor_visible(!game.player.mouse_control);
+    }
+
+    fn exit(&mut self, game: &mut Game) {
+        game.stop_emitters();
+    }
+
+    fn handle_input(&mut self, game: &mut Game) -> Option<SceneEvent> {
+        if game.input.is_pressed(Key::Escape, KeyRepeat::No)
+            || game.settings.bindings.is_pressed(&game.input, Action::Pause)
+        {
+            return Some(SceneEvent::Pause);
+        }
+
+        // Guardado y carga rápidos
+        if game.input.is_pressed(Key::F5, KeyRepeat::No) {
+            game.save_game();
+        }
+        if game.input.is_pressed(Key::F9, KeyRepeat::No) {
+            match game.read_save() {
+                Ok(state) => {
+                    game.restore(state);
+                    game.show_notice("GAME LOADED");
+                }
+                Err(e) => {
+                    eprintln!("could not load the saved game: {}", e);
+                    game.show_notice("COULD NOT LOAD SAVE");
+                }
+            }
+        }
+
+        game.handle_playing_input();
+        None
+    }
+
+    fn update(&mut self, game: &mut Game, dt: f32) -> Option<SceneEvent> {
+        if game.reached_goal() {
+            game.play_sfx("win2.mp3");
+            game.finish_run();
+            return Some(SceneEvent::Goal);
+        }
+
+        game.update_playing(dt);
+        None
+    }
+
+    fn render(&mut self, game: &mut Game) {
+        game.draw_playing();
+        draw_notice(game);
+    }
+}
@@ -0,0 +1,217 @@
+use super::menu::{draw_centered, Menu, MenuInput};
+use super::{Scene, SceneEvent};
+use crate::game::Game;
+use crate::input::{is_bindable, key_name, Action};
+use crate::minimap::MinimapShape;
+use crate::settings::{MAX_FOV, MAX_WINDOW_SCALE, MIN_FOV};
+use crate::sfx::Channel;
+use minifb::{Key, KeyRepeat};
+
+const DIM: f32 = 0.85;
+const FOV_STEP: f32 = 5.0;
+const VOLUME_STEP: f32 = 0.1;
+const SENSITIVITY_FACTOR: f32 = 1.25;
+const MENU_WIDTH: usize = 270;
+
+#[derive(Clone, Copy)]
+enum Item {
+    Fov,
+    Sensitivity,
+    MasterVolume,
+    Volume(Channel),
+    Resolution,
+    HeadBob,
+    LanternSway,
+    Footsteps,
+    ShuffleMusic,
+    MinimapRotate,
+    MinimapShape,
+    Binding(Action),
+    Back,
+}
+
+fn items() -> Vec<Item> {
+    let mut items = vec![Item::Fov, Item::Sensitivity, Item::MasterVolume];
+    items.extend(Channel::ALL.map(Item::Volume));
+    items.extend([
+        Item::Resolution,
+        Item::HeadBob,
+        Item::LanternSway,
+        Item::Footsteps,
+        Item::ShuffleMusic,
+        Item::MinimapRotate,
+        Item::MinimapShape,
+    ]);
+    items.extend(Action::ALL.map(Item::Binding));
+    items.push(Item::Back);
+    items
+}
+
+fn on_off(value: bool) -> String {
+    if value { "ON" } else { "OFF" }.to_string()
+}
+
+fn percent(value: f32) -> String {
+    format!("{}%", (value * 100.0).round())
+}
+
+// Ajustes del juego; se guardan al salir de la pantalla
+pub struct SettingsScene {
+    items: Vec<Item>,
+    menu: Menu,
+    capturing: Option<Action>, // Esperando la tecla nueva para esta acción
+}
+
+impl SettingsScene {
+    pub fn new() -> Self {
+        let items = items();
+        SettingsScene {
+            menu: Menu::new(items.len()),
+            items,
+            capturing: None,
+        }
+    }
+
+    fn row(&self, game: &Game, item: Item) -> (String, String) {
+        let settings = &game.settings;
+        match item {
+            Item::Fov => ("FIELD OF VIEW".into(), format!("{}", settings.fov.round())),
+            Item::Sensitivity => (
+                "MOUSE SENSITIVITY".into(),
+                format!("{:.1}", settings.mouse_sensitivity * 1000.0),
+            ),
+            Item::MasterVolume => ("MASTER VOLUME".into(), percent(settings.master_volume)),
+            Item::Volume(channel) => (
+                format!("{:?} VOLUME", channel),
+                percent(settings.channel_volume(channel)),
+            ),
+            Item::Resolution => (
+                "RESOLUTION SCALE".into(),
+                match settings.window_scale {
+                    Some(n) => format!("{}X", n),
+                    None => "FIT".into(),
+                },
+            ),
+            Item::HeadBob => ("HEAD BOB".into(), on_off(settings.head_bob)),
+            Item::LanternSway => ("LANTERN SWAY".into(), on_off(settings.lantern_sway)),
+            Item::Footsteps => ("FOOTSTEP SOUNDS".into(), on_off(settings.footstep_sounds)),
+            Item::ShuffleMusic => ("SHUFFLE MUSIC".into(), on_off(settings.shuffle_music)),
+            Item::MinimapRotate => ("ROTATE MINIMAP".into(), on_off(settings.minimap_rotate)),
+            Item::MinimapShape => (
+                "MINIMAP SHAPE".into(),
+                settings.minimap_shape.name().to_uppercase(),
+            ),
+            Item::Binding(action) => {
+                let key = if self.capturing == Some(action) {
+                    "PRESS A KEY"
+                } else {
+                    key_name(settings.bindings.key(action)).unwrap_or("?")
+                };
+                (action.name().replace('_', " "), key.to_string())
+            }
+            Item::Back => ("BACK".into(), String::new()),
+        }
+    }
+
+    // Cambia el valor de la opción; `step` es -1 o 1
+    fn adjust(&mut self, game: &mut Game, item: Item, step: f32) {
+        let settings = &mut game.settings;
+        match item {
+            Item::Fov => {
+                settings.fov = ((settings.fov / FOV_STEP).round() + step) * FOV_STEP;
+                settings.fov = settings.fov.clamp(MIN_FOV, MAX_FOV);
+            }
+            Item::Sensitivity => {
+                game.player.adjust_mouse_sensitivity(SENSITIVITY_FACTOR.powf(step));
+                settings.mouse_sensitivity = game.player.mouse_sensitivity;
+            }
+            Item::MasterVolume => {
+                settings.master_volume = (settings.master_volume + step * VOLUME_STEP).clamp(0.0, 1.0);
+            }
+            Item::Volume(channel) => {
+                let volume = settings.channel_volume(channel) + step * VOLUME_STEP;
+                settings.set_channel_volume(channel, volume);
+            }
+            Item::Resolution => {
+                // FIT va antes de 1X
+                let current = settings.window_scale.unwrap_or(0) as i32;
+                let next = (current + step as i32).clamp(0, MAX_WINDOW_SCALE as i32) as usize;
+                if next != current as usize {
+                    game.set_window_scale(if next == 0 { None } else { Some(next) });
+                }
+            }
+            Item::HeadBob => settings.head_bob = !settings.head_bob,
+            Item::LanternSway => settings.lantern_sway = !settings.lantern_sway,
+            Item::Footsteps => settings.footstep_sounds = !settings.footstep_sounds,
+            Item::ShuffleMusic => settings.shuffle_music = !settings.shuffle_music,
+            Item::MinimapRotate => settings.minimap_rotate = !settings.minimap_rotate,
+            Item::MinimapShape => {
+                settings.minimap_shape = match settings.minimap_shape {
+                    MinimapShape::Circle => MinimapShape::Square,
+                    MinimapShape::Square => MinimapShape::Circle,
+                }
+            }
+            Item::Binding(_) | Item::Back => {}
+        }
+        game.apply_settings();
+    }
+
+    // Toma la primera tecla asignable que se presione; Escape cancela
+    fn capture_key(&mut self, game: &mut Game, action: Action) {
+        for key in game.input.pressed_keys().to_vec() {
+            if key == Key::Escape {
+                self.capturing = None;
+                return;
+            }
+            if is_bindable(key) {
+                game.settings.bindings.set(action, key);
+                game.play_ui_sound("mapSelect.mp3");
+                self.capturing = None;
+                return;
+            }
+        }
+    }
+}
+
+impl Scene for SettingsScene {
+    fn exit(&mut self, game: &mut Game) {
+        game.save_settings();
+    }
+
+    fn handle_input(&mut self, game: &mut Game) -> Option<SceneEvent> {
+        if let Some(action) = self.capturing {
+            self.capture_key(game, action);
+            return None;
+        }
+
+        if game.input.is_pressed(Key::Escape, KeyRepeat::No) {
+            return Some(SceneEvent::Back);
+        }
+
+        let item = self.items[self.menu.selected];
+        match (self.menu.navigate(game), item) {
+            (MenuInput::Select, Item::Back) => return Some(SceneEvent::Back),
+            (MenuInput::Select, Item::Binding(action)) => self.capturing = Some(action),
+            (MenuInput::Left, item) => self.adjust(game, item, -1.0),
+            (MenuInput::Right | MenuInput::Select, item) => self.adjust(game, item, 1.0),
+            (MenuInput::None, _) => {}
+        }
+        None
+    }
+
+    fn render(&mut self, game: &mut Game) {
+        game.framebuffer.overlay(0x000000, DIM);
+        draw_centered(&mut game.framebuffer, 8, "SETTINGS", 0xffffff);
+
+        let rows: Vec<(String, String)> = self
+            .items
+            .iter()
+            .map(|&item| self.row(game, item))
+            .collect();
+        self.menu.draw(&mut game.framebuffer, MENU_WIDTH, 22, &rows);
+    }
+
+    fn is_overlay(&self) -> bool {
+        true
+    }
+}
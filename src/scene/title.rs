@@ -0,0 +1,59 @@
+use super::menu::{draw_centered, draw_notice};
+use super::{Scene, SceneEvent};
+use crate::game::{Game, Level, TITLE_MUSIC};
+use minifb::{Key, KeyRepeat};
+
+// Pantalla de título: se elige el laberinto con 1, 2 o 3
+pub struct TitleScene;
+
+impl Scene for TitleScene {
+    fn enter(&mut self, game: &mut Game) {
+        game.music.play(&mut game.audio, TITLE_MUSIC, true);
+        game.set_cursor_visible(true);
+    }
+
+    fn handle_input(&mut self, game: &mut Game) -> Option<SceneEvent> {
+        for (key, n) in [(Key::Key1, 1), (Key::Key2, 2), (Key::Key3, 3)] {
+            if game.input.is_pressed(key, KeyRepeat::No) {
+                game.play_ui_sound("mapSelect.mp3");
+                game.level = Level::Builtin(n);
+                return Some(SceneEvent::Start);
+            }
+        }
+
+        if game.has_save && game.input.is_pressed(Key::C, KeyRepeat::No) {
+            match game.read_save() {
+                Ok(state) => {
+                    game.play_ui_sound("mapSelect.mp3");
+                    game.level = state.level.clone();
+                    game.pending_save = Some(state);
+                    return Some(SceneEvent::Start);
+                }
+                Err(e) => {
+                    eprintln!("could not load the saved game: {}", e);
+                    game.show_notice("COULD NOT LOAD SAVE");
+                }
+            }
+        }
+
+        if game.input.is_pressed(Key::S, KeyRepeat::No) {
+            return Some(SceneEvent::Settings);
+        }
+
+        if game.input.is_pressed(Key::Escape, KeyRepeat::No) {
+            return Some(SceneEvent::Quit);
+        }
+        None
+    }
+
+    fn render(&mut self, game: &mut Game) {
+        game.draw_screen("./src/img/tittleScreen.png");
+        // Las opciones van pegadas al borde de abajo
+        let bottom = game.framebuffer.height;
+        if game.has_save {
+            draw_centered(&mut game.framebuffer, bottom.saturating_sub(22), "C: CONTINUE", 0xffffff);
+        }
+        draw_centered(&mut game.framebuffer, bottom.saturating_sub(12), "S: SETTINGS", 0xffffff);
+        draw_notice(game);
+    }
+}
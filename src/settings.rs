@@ -0,0 +1,61 @@
+use crate::input::Bindings;
+use crate::minimap::MinimapShape;
+use crate::sfx::Channel;
+
+pub const MIN_FOV: f32 = 40.0;
+pub const MAX_FOV: f32 = 100.0;
+pub const MAX_WINDOW_SCALE: usize = 4;
+
+// Opciones que el jugador puede cambiar desde el menú de ajustes; se guardan
+// junto con el resto de la configuración (ver config.rs)
+#[derive(Clone)]
+pub struct Settings {
+    pub head_bob: bool,
+    pub lantern_sway: bool,
+    pub footstep_sounds: bool,
+    pub shuffle_music: bool, // En los laberintos, lista aleatoria en vez de la pista del nivel
+    pub fov: f32, // Grados
+    pub mouse_sensitivity: f32,
+    pub master_volume: f32,
+    pub channel_volumes: [(Channel, f32); 4],
+    pub window_scale: Option<usize>, // Múltiplo del framebuffer; None = ajustar a la pantalla
+    pub minimap_rotate: bool, // El mapa de la esquina gira para que adelante quede arriba
+    pub minimap_shape: MinimapShape,
+    pub bindings: Bindings,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            head_bob: true,
+            lantern_sway: true,
+            footstep_sounds: true,
+            shuffle_music: false,
+            fov: 180.0 / 3.5,
+            mouse_sensitivity: 0.005,
+            master_volume: 1.0,
+            channel_volumes: Channel::ALL.map(|channel| (channel, channel.default_volume())),
+            window_scale: None,
+            minimap_rotate: false,
+            minimap_shape: MinimapShape::Circle,
+            bindings: Bindings::default(),
+        }
+    }
+}
+
+impl Settings {
+    pub fn channel_volume(&self, channel: Channel) -> f32 {
+        self.channel_volumes
+            .iter()
+            .find(|(c, _)| *c == channel)
+            .map_or(1.0, |(_, volume)| *volume)
+    }
+
+    pub fn set_channel_volume(&mut self, channel: Channel, volume: f32) {
+        for (c, v) in self.channel_volumes.iter_mut() {
+            if *c == channel {
+                *v = volume.clamp(0.0, 1.0);
+            }
+        }
+    }
+}
@@ -1,44 +1,406 @@
-use rodio::{Decoder, OutputStream, Sink, Source};
-use std::io::BufReader;
-use std::fs::File;
-use std::thread;
-
-pub fn play_sound(file_path: &str) {
-    // Clone file_path into a String to move it into the new thread
-    let file_path = file_path.to_string();
-
-    thread::spawn(move || {
-        // Create an output stream and a sink to control the playback
-        let (_stream, stream_handle) = OutputStream::try_default().unwrap();
-        let sink = Sink::try_new(&stream_handle).unwrap();
-
-        // Load the audio file
-        let file = File::open(file_path).unwrap();
-        let source = Decoder::new(BufReader::new(file)).unwrap();
-
-        // Play the sound
-        sink.append(source);
-        sink.sleep_until_end(); // Wait until the sound finishes playing
-    });
+use crate::audio_backend::{
+    AudioBackend, AudioError, AudioLog, RecordingBackend, Reverb, RodioBackend, SilentBackend,
+    SoundRequest, SoundSource,
+};
+use crate::sound_cache::{SoundCache, SoundManifest, MANIFEST_PATH, SOUND_DIR};
+use crate::synth;
+use std::collections::HashMap;
+use std::time::Duration;
+
+// Canales de audio; cada uno tiene su propio volumen
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Channel {
+    Music,
+    Sfx,
+    Ambience,
+    Ui,
+}
+
+impl Channel {
+    pub const ALL: [Channel; 4] = [Channel::Music, Channel::Sfx, Channel::Ambience, Channel::Ui];
+
+    pub fn default_volume(self) -> f32 {
+        match self {
+            Channel::Music => 0.6,
+            Channel::Sfx => 1.0,
+            Channel::Ambience => 0.8,
+            Channel::Ui => 1.0,
+        }
+    }
+}
+
+// Identificador de un sonido en reproducción, sirve para detenerlo después
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct SoundHandle(u64);
+
+struct PlayingSound {
+    channel: Channel,
+    gain: f32, // Volumen propio del sonido, se multiplica por el del canal
+}
+
+// Punto único de acceso al audio: todos los sonidos del juego pasan por aquí
+// y el backend se encarga de la salida real
+pub struct AudioManager {
+    backend: Box<dyn AudioBackend>,
+    cache: SoundCache,
+    manifest: Option<SoundManifest>,
+    sounds: HashMap<SoundHandle, PlayingSound>,
+    channel_volumes: HashMap<Channel, f32>,
+    master_volume: f32,
+    muted: bool,
+    paused: bool,
+    reverb: Option<Reverb>, // Eco del lugar, se aplica a los efectos
+    next_id: u64,
+}
+
+impl AudioManager {
+    // Usa el dispositivo de audio si existe; si no, sigue sin sonido
+    pub fn new() -> Self {
+        match RodioBackend::new() {
+            Ok(backend) => Self::with_backend(Box::new(backend)),
+            Err(e) => {
+                eprintln!("{}, continuing without sound", e);
+                Self::with_backend(Box::new(SilentBackend))
+            }
+        }
+    }
+
+    // Sin sonido pero registrando cada evento de audio
+    pub fn recording() -> (Self, AudioLog) {
+        let backend = RecordingBackend::new();
+        let log = backend.log();
+        (Self::with_backend(Box::new(backend)), log)
+    }
+
+    pub fn with_backend(backend: Box<dyn AudioBackend>) -> Self {
+        let manifest = match SoundManifest::load(MANIFEST_PATH) {
+            Ok(manifest) => Some(manifest),
+            Err(e) => {
+                eprintln!("{}, sounds will be loaded on demand", e);
+                None
+            }
+        };
+
+        // Sonidos generados para los emisores del laberinto
+        let mut cache = SoundCache::default();
+        cache.insert_persistent("hum", synth::machine_hum());
+        cache.insert_persistent("drip", synth::water_drip());
+        // Pisadas de los pisos que no tienen archivo propio y el golpe
+        // contra la pared
+        cache.insert_persistent("steps_dirt", synth::dirt_steps());
+        cache.insert_persistent("steps_water", synth::water_steps());
+        cache.insert_persistent("bump", synth::wall_bump());
+
+        AudioManager {
+            backend,
+            cache,
+            manifest,
+            sounds: HashMap::new(),
+            channel_volumes: Channel::ALL
+                .iter()
+                .map(|&channel| (channel, channel.default_volume()))
+                .collect(),
+            master_volume: 1.0,
+            muted: false,
+            paused: false,
+            reverb: None,
+            next_id: 0,
+        }
+    }
+
+    // Decodifica de antemano los sonidos que usa un nivel según el manifiesto
+    pub fn preload_level(&mut self, level: &str) -> Vec<AudioError> {
+        match &self.manifest {
+            Some(manifest) => self.cache.preload_level(manifest, level),
+            None => Vec::new(),
+        }
+    }
+
+    // Reproduce un sonido completo una sola vez desde memoria
+    pub fn play(&mut self, channel: Channel, name: &str) -> Result<SoundHandle, AudioError> {
+        let data = self.cache.get(name)?;
+        self.start(SoundRequest {
+            name: name.to_string(),
+            source: SoundSource::Buffered(data),
+            channel,
+            looped: false,
+            clip: None,
+            spatial: false,
+            reverb: None,
+        })
+    }
+
+    // Lee el archivo mientras suena en lugar de decodificarlo antes; pensado
+    // para pistas largas como la música
+    pub fn play_stream(
+        &mut self,
+        channel: Channel,
+        name: &str,
+        looped: bool,
+    ) -> Result<SoundHandle, AudioError> {
+        self.start(SoundRequest {
+            name: name.to_string(),
+            source: SoundSource::Stream(format!("{}{}", SOUND_DIR, name)),
+            channel,
+            looped,
+            clip: None,
+            spatial: false,
+            reverb: None,
+        })
+    }
+
+    // Reproduce solo un fragmento del sonido
+    pub fn play_clip(
+        &mut self,
+        channel: Channel,
+        name: &str,
+        start: Duration,
+        length: Duration,
+    ) -> Result<SoundHandle, AudioError> {
+        let data = self.cache.get(name)?;
+        self.start(SoundRequest {
+            name: name.to_string(),
+            source: SoundSource::Buffered(data),
+            channel,
+            looped: false,
+            clip: Some((start, length)),
+            spatial: false,
+            reverb: None,
+        })
+    }
+
+    // Sonido en bucle ubicado en el mundo: su volumen y paneo se ajustan
+    // cada paso con set_gain y set_pan
+    pub fn play_spatial(&mut self, channel: Channel, name: &str) -> Result<SoundHandle, AudioError> {
+        let data = self.cache.get(name)?;
+        self.start(SoundRequest {
+            name: name.to_string(),
+            source: SoundSource::Buffered(data),
+            channel,
+            looped: true,
+            clip: None,
+            spatial: true,
+            reverb: None,
+        })
+    }
+
+    // Repetir el mismo volumen no llega al backend
+    pub fn set_gain(&mut self, handle: SoundHandle, gain: f32) {
+        let gain = gain.clamp(0.0, 1.0);
+        let Some(sound) = self.sounds.get_mut(&handle) else {
+            return;
+        };
+        if sound.gain == gain {
+            return;
+        }
+        sound.gain = gain;
+        let volume = self.volume_for(&self.sounds[&handle]);
+        self.backend.set_volume(handle, volume);
+    }
+
+    pub fn set_pan(&mut self, handle: SoundHandle, pan: f32) {
+        if self.sounds.contains_key(&handle) {
+            self.backend.set_pan(handle, pan);
+        }
+    }
+
+    pub fn stop(&mut self, handle: SoundHandle) {
+        if self.sounds.remove(&handle).is_some() {
+            self.backend.stop(handle);
+        }
+    }
+
+    // Eco que se agrega a los efectos que empiecen desde ahora; los que ya
+    // suenan se quedan como están
+    pub fn set_reverb(&mut self, reverb: Option<Reverb>) {
+        self.reverb = reverb;
+    }
+
+    pub fn is_playing(&self, handle: SoundHandle) -> bool {
+        self.sounds.contains_key(&handle)
+    }
+
+    // Si hay algún sonido sonando en el canal
+    pub fn channel_active(&self, channel: Channel) -> bool {
+        self.sounds.values().any(|sound| sound.channel == channel)
+    }
+
+    pub fn set_master_volume(&mut self, volume: f32) {
+        self.master_volume = volume.clamp(0.0, 1.0);
+        self.apply_volumes();
+    }
+
+    pub fn master_volume(&self) -> f32 {
+        self.master_volume
+    }
+
+    pub fn set_channel_volume(&mut self, channel: Channel, volume: f32) {
+        self.channel_volumes.insert(channel, volume.clamp(0.0, 1.0));
+        self.apply_volumes();
+    }
+
+    pub fn channel_volume(&self, channel: Channel) -> f32 {
+        self.channel_volumes[&channel]
+    }
+
+    pub fn toggle_mute(&mut self) {
+        self.muted = !self.muted;
+        self.apply_volumes();
+    }
+
+    // Los sonidos de interfaz siguen sonando en pausa para que los menús
+    // tengan sonido
+    pub fn pause(&mut self) {
+        self.paused = true;
+        for (&handle, sound) in &self.sounds {
+            if sound.channel != Channel::Ui {
+                self.backend.pause(handle);
+            }
+        }
+    }
+
+    pub fn resume(&mut self) {
+        self.paused = false;
+        for (&handle, sound) in &self.sounds {
+            if sound.channel != Channel::Ui {
+                self.backend.resume(handle);
+            }
+        }
+    }
+
+    // Libera los sonidos que ya terminaron
+    pub fn update(&mut self) {
+        let backend = &mut self.backend;
+        self.sounds.retain(|&handle, _| !backend.is_finished(handle));
+    }
+
+    fn start(&mut self, mut request: SoundRequest) -> Result<SoundHandle, AudioError> {
+        if request.channel == Channel::Sfx && !request.spatial {
+            request.reverb = self.reverb;
+        }
+
+        let handle = SoundHandle(self.next_id);
+        self.next_id += 1;
+
+        let sound = PlayingSound {
+            channel: request.channel,
+            gain: 1.0,
+        };
+        let paused = self.paused && request.channel != Channel::Ui;
+        self.backend.start(handle, &request, self.volume_for(&sound), paused)?;
+        self.sounds.insert(handle, sound);
+        Ok(handle)
+    }
+
+    fn volume_for(&self, sound: &PlayingSound) -> f32 {
+        if self.muted {
+            0.0
+        } else {
+            self.master_volume * self.channel_volumes[&sound.channel] * sound.gain
+        }
+    }
+
+    fn apply_volumes(&mut self) {
+        let volumes: Vec<(SoundHandle, f32)> = self
+            .sounds
+            .iter()
+            .map(|(&handle, sound)| (handle, self.volume_for(sound)))
+            .collect();
+
+        for (handle, volume) in volumes {
+            self.backend.set_volume(handle, volume);
+        }
+    }
 }
 
-pub fn play_background_music(file_path: &str) {
-    // Clone file_path into a String to move it into the new thread
-    let file_path = file_path.to_string();
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audio_backend::AudioEventKind;
+
+    // Lo que registró el backend, sin los tiempos
+    fn recorded(log: &AudioLog) -> Vec<(SoundHandle, String)> {
+        log.events()
+            .into_iter()
+            .map(|event| {
+                let kind = match event.kind {
+                    AudioEventKind::Started(request) => format!("started {}", request.name),
+                    AudioEventKind::Stopped => "stopped".to_string(),
+                    AudioEventKind::VolumeChanged(volume) => format!("volume {:.2}", volume),
+                    AudioEventKind::Paused => "paused".to_string(),
+                    AudioEventKind::Resumed => "resumed".to_string(),
+                    AudioEventKind::PanChanged(pan) => format!("pan {:.2}", pan),
+                };
+                (event.handle, kind)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn play_records_started_in_order() {
+        let (mut audio, log) = AudioManager::recording();
+        let win = audio.play(Channel::Sfx, "win2.mp3").unwrap();
+        let music = audio.play_stream(Channel::Music, "background.mp3", true).unwrap();
 
-    thread::spawn(move || {
-        // Create an output stream and a sink to control the playback
-        let (_stream, stream_handle) = OutputStream::try_default().unwrap();
-        let sink = Sink::try_new(&stream_handle).unwrap();
+        assert_ne!(win, music);
+        assert_eq!(
+            recorded(&log),
+            vec![(win, "started win2.mp3".to_string()), (music, "started background.mp3".to_string())]
+        );
+        let events = log.events();
+        assert!(events[0].at <= events[1].at);
+    }
 
-        // Load the audio file
-        let file = File::open(file_path).unwrap();
-        let source = Decoder::new(BufReader::new(file)).unwrap().repeat_infinite();
+    #[test]
+    fn stop_by_handle_only_stops_that_sound() {
+        let (mut audio, log) = AudioManager::recording();
+        let music = audio.play_stream(Channel::Music, "background.mp3", true).unwrap();
+        let steps = audio.play_stream(Channel::Sfx, "steps.mp3", true).unwrap();
+        audio.stop(music);
+        // Detener otra vez no vuelve a llegar al backend
+        audio.stop(music);
 
-        // Play the music
-        sink.append(source);
+        let events = recorded(&log);
+        assert_eq!(events.last(), Some(&(music, "stopped".to_string())));
+        assert_eq!(events.iter().filter(|(_, kind)| kind == "stopped").count(), 1);
 
-        // Keep the thread alive while the music is playing
-        sink.sleep_until_end(); // Wait until the music finishes playing
-    });
-}
\ No newline at end of file
+        // Lo que no está en bucle termina enseguida en el backend de registro
+        audio.update();
+        audio.stop(steps);
+        assert_eq!(recorded(&log).last(), Some(&(steps, "stopped".to_string())));
+    }
+
+    #[test]
+    fn pause_and_resume_skip_ui_sounds() {
+        let (mut audio, log) = AudioManager::recording();
+        let music = audio.play_stream(Channel::Music, "background.mp3", true).unwrap();
+        let click = audio.play_stream(Channel::Ui, "mapSelect.mp3", true).unwrap();
+        audio.pause();
+        audio.resume();
+
+        let events = recorded(&log);
+        assert!(events.contains(&(music, "paused".to_string())));
+        assert!(events.contains(&(music, "resumed".to_string())));
+        // Los menús de pausa necesitan sus sonidos
+        assert!(!events.iter().any(|(handle, kind)| *handle == click && kind != "started mapSelect.mp3"));
+    }
+
+    #[test]
+    fn mute_silences_and_restores_every_sound() {
+        let (mut audio, log) = AudioManager::recording();
+        let music = audio.play_stream(Channel::Music, "background.mp3", true).unwrap();
+        let steps = audio.play_stream(Channel::Sfx, "steps.mp3", true).unwrap();
+
+        audio.toggle_mute();
+        let muted = recorded(&log);
+        assert!(muted.contains(&(music, "volume 0.00".to_string())));
+        assert!(muted.contains(&(steps, "volume 0.00".to_string())));
+
+        audio.toggle_mute();
+        let restored = recorded(&log);
+        let music_volume = format!("volume {:.2}", Channel::Music.default_volume());
+        assert_eq!(restored.len(), muted.len() + 2);
+        assert!(restored[muted.len()..].contains(&(music, music_volume)));
+        assert!(restored[muted.len()..].contains(&(steps, "volume 1.00".to_string())));
+    }
+}
@@ -0,0 +1,190 @@
+use crate::audio_backend::AudioError;
+use rodio::{Decoder, Source};
+use std::collections::{HashMap, HashSet};
+use std::fs::{self, File};
+use std::io::BufReader;
+use std::sync::Arc;
+use std::time::Duration;
+
+pub const SOUND_DIR: &str = "./src/sound/";
+pub const MANIFEST_PATH: &str = "./src/sound/manifest.txt";
+
+// Sonido ya decodificado en memoria
+pub struct SoundData {
+    pub channels: u16,
+    pub sample_rate: u32,
+    pub samples: Vec<i16>,
+}
+
+impl SoundData {
+    pub fn decode(path: &str) -> Result<Self, AudioError> {
+        let file = File::open(path).map_err(|error| AudioError::Io {
+            path: path.to_string(),
+            error,
+        })?;
+        let decoder = Decoder::new(BufReader::new(file)).map_err(|e| AudioError::Decode {
+            path: path.to_string(),
+            reason: e.to_string(),
+        })?;
+
+        Ok(SoundData {
+            channels: decoder.channels(),
+            sample_rate: decoder.sample_rate(),
+            samples: decoder.collect(),
+        })
+    }
+
+    // Índice de muestra correspondiente a un instante, alineado al cuadro
+    fn sample_index(&self, at: Duration) -> usize {
+        let frame = (at.as_secs_f64() * self.sample_rate as f64) as usize;
+        (frame * self.channels as usize).min(self.samples.len())
+    }
+}
+
+// Fuente de rodio que lee de un SoundData compartido; clonarla no copia las
+// muestras
+#[derive(Clone)]
+pub struct BufferedSound {
+    data: Arc<SoundData>,
+    start: usize,
+    end: usize,
+    pos: usize,
+    looped: bool,
+}
+
+impl BufferedSound {
+    pub fn new(data: Arc<SoundData>, clip: Option<(Duration, Duration)>, looped: bool) -> Self {
+        let (start, end) = match clip {
+            Some((start, length)) => (data.sample_index(start), data.sample_index(start + length)),
+            None => (0, data.samples.len()),
+        };
+
+        BufferedSound {
+            data,
+            start,
+            end,
+            pos: start,
+            looped,
+        }
+    }
+}
+
+impl Iterator for BufferedSound {
+    type Item = i16;
+
+    fn next(&mut self) -> Option<i16> {
+        if self.pos >= self.end {
+            if !self.looped || self.start >= self.end {
+                return None;
+            }
+            self.pos = self.start;
+        }
+
+        let sample = self.data.samples[self.pos];
+        self.pos += 1;
+        Some(sample)
+    }
+}
+
+impl Source for BufferedSound {
+    fn current_frame_len(&self) -> Option<usize> {
+        None
+    }
+
+    fn channels(&self) -> u16 {
+        self.data.channels
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.data.sample_rate
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        None
+    }
+}
+
+// Secciones del manifiesto: qué archivos necesita cada nivel
+pub struct SoundManifest {
+    sections: HashMap<String, Vec<String>>,
+}
+
+impl SoundManifest {
+    // Formato: `[nivel]` abre una sección y cada línea siguiente es un
+    // archivo dentro de SOUND_DIR; `#` inicia un comentario
+    pub fn load(path: &str) -> Result<Self, AudioError> {
+        let text = fs::read_to_string(path).map_err(|error| AudioError::Io {
+            path: path.to_string(),
+            error,
+        })?;
+
+        let mut sections: HashMap<String, Vec<String>> = HashMap::new();
+        let mut current = String::from("common");
+
+        for line in text.lines() {
+            let line = line.split('#').next().unwrap_or("").trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+                current = name.trim().to_string();
+                sections.entry(current.clone()).or_default();
+            } else {
+                sections.entry(current.clone()).or_default().push(line.to_string());
+            }
+        }
+
+        Ok(SoundManifest { sections })
+    }
+
+    // Archivos de la sección común más los del nivel
+    fn sounds_for(&self, level: &str) -> Vec<String> {
+        ["common", level]
+            .iter()
+            .filter_map(|section| self.sections.get(*section))
+            .flatten()
+            .cloned()
+            .collect()
+    }
+}
+
+// Sonidos decodificados una sola vez y reproducidos desde memoria
+#[derive(Default)]
+pub struct SoundCache {
+    sounds: HashMap<String, Arc<SoundData>>,
+    persistent: HashSet<String>, // Sonidos generados que no vienen de archivo
+}
+
+impl SoundCache {
+    // Devuelve el sonido ya cargado o lo decodifica en ese momento
+    pub fn get(&mut self, name: &str) -> Result<Arc<SoundData>, AudioError> {
+        if let Some(data) = self.sounds.get(name) {
+            return Ok(data.clone());
+        }
+
+        let data = Arc::new(SoundData::decode(&format!("{}{}", SOUND_DIR, name))?);
+        self.sounds.insert(name.to_string(), data.clone());
+        Ok(data)
+    }
+
+    // Agrega un sonido generado en memoria; nunca se libera
+    pub fn insert_persistent(&mut self, name: &str, data: SoundData) {
+        self.sounds.insert(name.to_string(), Arc::new(data));
+        self.persistent.insert(name.to_string());
+    }
+
+    // Carga los sonidos de un nivel y libera los que ya no se usan. Los
+    // archivos que fallan se reportan pero no impiden cargar el resto
+    pub fn preload_level(&mut self, manifest: &SoundManifest, level: &str) -> Vec<AudioError> {
+        let wanted: HashSet<String> = manifest.sounds_for(level).into_iter().collect();
+        let persistent = &self.persistent;
+        self.sounds
+            .retain(|name, _| wanted.contains(name) || persistent.contains(name));
+
+        wanted
+            .iter()
+            .filter_map(|name| self.get(name).err())
+            .collect()
+    }
+}
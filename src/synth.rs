@@ -0,0 +1,108 @@
+use crate::rng::Rng;
+use crate::sound_cache::SoundData;
+use std::f32::consts::TAU;
+
+const SAMPLE_RATE: u32 = 22050;
+
+// Los juegos de pisadas traen varias pisadas seguidas, una cada
+// STEP_SPACING segundos, y se reproducen por fragmentos como steps.mp3
+const STEP_COUNT: usize = 4;
+const STEP_SPACING: f32 = 0.4;
+
+fn render(seconds: f32, mut sample: impl FnMut(f32) -> f32) -> SoundData {
+    let count = (seconds * SAMPLE_RATE as f32) as usize;
+    let samples = (0..count)
+        .map(|n| {
+            let t = n as f32 / SAMPLE_RATE as f32;
+            (sample(t).clamp(-1.0, 1.0) * i16::MAX as f32) as i16
+        })
+        .collect();
+
+    SoundData {
+        channels: 1,
+        sample_rate: SAMPLE_RATE,
+        samples,
+    }
+}
+
+// Zumbido de máquina: 55 Hz con armónicos y un leve pulso. Dura un segundo
+// exacto para que el bucle no tenga saltos
+pub fn machine_hum() -> SoundData {
+    render(1.0, |t| {
+        let pulse = 0.85 + 0.15 * (TAU * 2.0 * t).sin();
+        let tone = 0.5 * (TAU * 55.0 * t).sin()
+            + 0.3 * (TAU * 110.0 * t).sin()
+            + 0.15 * (TAU * 165.0 * t).sin();
+        0.6 * pulse * tone
+    })
+}
+
+// Gota de agua: un tono que baja rápido y se apaga, seguido de silencio
+pub fn water_drip() -> SoundData {
+    render(1.6, |t| {
+        let drop = |start: f32, gain: f32| {
+            let t = t - start;
+            if !(0.0..0.12).contains(&t) {
+                return 0.0;
+            }
+            let frequency = 1400.0 - 5000.0 * t;
+            gain * (-t * 40.0).exp() * (TAU * frequency * t).sin()
+        };
+        drop(0.0, 0.8) + drop(0.95, 0.4)
+    })
+}
+
+// Ruido blanco entre -1 y 1
+fn noise(rng: &mut Rng) -> f32 {
+    (rng.next_u64() >> 40) as f32 / (1u64 << 24) as f32 * 2.0 - 1.0
+}
+
+// Número de pisada y tiempo desde que empezó; cada pisada suena un poco
+// distinta a la anterior
+fn step_at(t: f32) -> (f32, f32) {
+    let index = (t / STEP_SPACING).floor();
+    (index, t - index * STEP_SPACING)
+}
+
+// Pisadas sobre tierra: ruido grave y apagado con un golpe sordo
+pub fn dirt_steps() -> SoundData {
+    let mut rng = Rng::new(7);
+    let mut low = 0.0;
+    render(STEP_COUNT as f32 * STEP_SPACING, move |t| {
+        let (index, t) = step_at(t);
+        let pitch = 1.0 + 0.07 * index;
+        low += 0.12 * (noise(&mut rng) - low); // Filtro pasa bajos
+        let envelope = (-t * 28.0 * pitch).exp();
+        envelope * (2.0 * low + 0.35 * (TAU * 70.0 * pitch * t).sin())
+    })
+}
+
+// Pisadas en agua: salpicadura de ruido agudo y una burbuja que sube de tono
+pub fn water_steps() -> SoundData {
+    let mut rng = Rng::new(11);
+    let mut low = 0.0;
+    render(STEP_COUNT as f32 * STEP_SPACING, move |t| {
+        let (index, t) = step_at(t);
+        low += 0.45 * (noise(&mut rng) - low);
+        let splash = 0.6 * (-t * 11.0).exp() * low;
+
+        let bubble_t = t - 0.04;
+        let bubble = if bubble_t > 0.0 {
+            let frequency = 450.0 + 80.0 * index + 2500.0 * bubble_t;
+            0.25 * (-bubble_t * 30.0).exp() * (TAU * frequency * bubble_t).sin()
+        } else {
+            0.0
+        };
+        splash + bubble
+    })
+}
+
+// Golpe contra la pared: tono muy grave que se apaga rápido
+pub fn wall_bump() -> SoundData {
+    let mut rng = Rng::new(3);
+    render(0.3, move |t| {
+        let thud = 0.8 * (TAU * 60.0 * t).sin() + 0.3 * (TAU * 95.0 * t).sin();
+        let click = 0.3 * noise(&mut rng) * (-t * 120.0).exp();
+        (-t * 18.0).exp() * thud + click
+    })
+}
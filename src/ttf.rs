@@ -0,0 +1,146 @@
+use crate::font::Align;
+use crate::framebuffer::Framebuffer;
+use ab_glyph::{point, Font, FontRef, GlyphId, PxScale, ScaleFont};
+use std::collections::HashMap;
+
+// DejaVu Sans (licencia Bitstream Vera, ver src/fonts); trae los acentos y
+// signos del español
+const BUNDLED: &[u8] = include_bytes!("fonts/DejaVuSans.ttf");
+
+// Cobertura de un glifo ya rasterizado, relativa a la línea base
+struct CachedGlyph {
+    left: i32,
+    top: i32,
+    width: usize,
+    coverage: Vec<f32>, // 0 = vacío, 1 = cubierto; fila por fila
+}
+
+// Cómo se dibuja un texto con la fuente TrueType
+#[derive(Clone, Copy, Debug)]
+pub struct TtfStyle {
+    pub size: f32, // Alto en píxeles
+    pub color: u32,
+    pub align: Align,
+    pub shadow: Option<u32>, // Un píxel abajo a la derecha
+}
+
+impl Default for TtfStyle {
+    fn default() -> Self {
+        TtfStyle {
+            size: 16.0,
+            color: 0xffffff,
+            align: Align::Left,
+            shadow: None,
+        }
+    }
+}
+
+// Fuente TrueType con los glifos en caché por tamaño, para no rasterizar
+// lo mismo en cada cuadro
+pub struct TrueTypeFont {
+    font: FontRef<'static>,
+    cache: HashMap<(GlyphId, u32), Option<CachedGlyph>>,
+}
+
+impl TrueTypeFont {
+    pub fn bundled() -> Self {
+        TrueTypeFont {
+            font: FontRef::try_from_slice(BUNDLED).expect("bundled font is valid"),
+            cache: HashMap::new(),
+        }
+    }
+
+    pub fn line_height(&self, size: f32) -> f32 {
+        let scaled = self.font.as_scaled(PxScale::from(size));
+        scaled.ascent() - scaled.descent() + scaled.line_gap()
+    }
+
+    // Ancho de una línea contando el kerning entre pares de letras
+    pub fn text_width(&self, text: &str, size: f32) -> f32 {
+        let scaled = self.font.as_scaled(PxScale::from(size));
+        let mut width = 0.0;
+        let mut previous = None;
+        for ch in text.chars() {
+            let id = scaled.glyph_id(ch);
+            if let Some(previous) = previous {
+                width += scaled.kern(previous, id);
+            }
+            width += scaled.h_advance(id);
+            previous = Some(id);
+        }
+        width
+    }
+
+    // Dibuja el texto con `y` en el borde de arriba; cada '\n' empieza otra
+    // línea. Devuelve el alto en píxeles de lo que dibujó
+    pub fn draw(&mut self, framebuffer: &mut Framebuffer, x: usize, y: usize, text: &str, style: &TtfStyle) -> usize {
+        let line_height = self.line_height(style.size);
+        let ascent = self.font.as_scaled(PxScale::from(style.size)).ascent();
+        let lines: Vec<&str> = text.split('\n').collect();
+
+        for (i, line) in lines.iter().enumerate() {
+            let width = self.text_width(line, style.size);
+            let left = match style.align {
+                Align::Left => x as f32,
+                Align::Center => x as f32 - width / 2.0,
+                Align::Right => x as f32 - width,
+            };
+            let baseline = y as f32 + i as f32 * line_height + ascent;
+
+            // Primero la sombra para que el texto quede encima
+            let passes = style.shadow.map(|color| (color, 1.0)).into_iter().chain([(style.color, 0.0)]);
+            for (color, offset) in passes {
+                self.draw_line(framebuffer, left + offset, baseline + offset, line, style.size, color);
+            }
+        }
+        (lines.len() as f32 * line_height).ceil() as usize
+    }
+
+    fn draw_line(&mut self, framebuffer: &mut Framebuffer, x: f32, baseline: f32, text: &str, size: f32, color: u32) {
+        let scale = PxScale::from(size);
+        let mut pen = x;
+        let mut previous = None;
+        for ch in text.chars() {
+            let (id, advance, kern) = {
+                let scaled = self.font.as_scaled(scale);
+                let id = scaled.glyph_id(ch);
+                (id, scaled.h_advance(id), previous.map_or(0.0, |previous| scaled.kern(previous, id)))
+            };
+            pen += kern;
+            if let Some(glyph) = self.glyph(id, size) {
+                let left = pen.round() as i32 + glyph.left;
+                let top = baseline.round() as i32 + glyph.top;
+                for (i, &alpha) in glyph.coverage.iter().enumerate() {
+                    let px = left + (i % glyph.width) as i32;
+                    let py = top + (i / glyph.width) as i32;
+                    if px >= 0 && py >= 0 {
+                        framebuffer.blend_point(px as usize, py as usize, color, alpha);
+                    }
+                }
+            }
+            pen += advance;
+            previous = Some(id);
+        }
+    }
+
+    // Los espacios y otros glifos sin contorno quedan como None
+    fn glyph(&mut self, id: GlyphId, size: f32) -> Option<&CachedGlyph> {
+        let font = &self.font;
+        self.cache
+            .entry((id, size.to_bits()))
+            .or_insert_with(|| {
+                let outlined = font.outline_glyph(id.with_scale_and_position(size, point(0.0, 0.0)))?;
+                let bounds = outlined.px_bounds();
+                let width = bounds.width() as usize;
+                let mut coverage = vec![0.0; width * bounds.height() as usize];
+                outlined.draw(|x, y, c| coverage[y as usize * width + x as usize] = c.min(1.0));
+                Some(CachedGlyph {
+                    left: bounds.min.x as i32,
+                    top: bounds.min.y as i32,
+                    width,
+                    coverage,
+                })
+            })
+            .as_ref()
+    }
+}